@@ -0,0 +1,719 @@
+use crate::disassembly::{cache_op_name, Disassembly, Instruction, Operand, Operation, Target, CP0_REG_NAMES, CPU_REG_NAMES, O32_REG_NAMES};
+use crate::encoder::{layout, EncodeError, Slot};
+use crate::rsp::{RSP_CP0_REG_NAMES, VU_ACC_NAMES, VU_CTRL_REG_NAMES};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Why a line of assembly couldn't be assembled.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AsmErrorKind {
+    UnknownMnemonic(String),
+    UnknownDirective(String),
+    /// The number of operands given, and the range the instruction accepts.
+    OperandCount(usize, usize, usize),
+    BadOperand(String),
+    UndefinedSymbol(String),
+    DuplicateLabel(String),
+    /// An operand or data value that doesn't fit its field.
+    OutOfRange(String),
+    /// An instruction that doesn't start on a word boundary.
+    Misaligned(u32),
+    /// The parsed instruction was rejected by the encoder.
+    Encode(EncodeError),
+}
+
+impl From<EncodeError> for AsmErrorKind {
+    fn from(err: EncodeError) -> Self {
+        AsmErrorKind::Encode(err)
+    }
+}
+
+/// An [`AsmErrorKind`] along with the 1-based line it occurred on.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AsmError {
+    pub line: usize,
+    pub kind: AsmErrorKind,
+}
+
+impl Display for AsmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use AsmErrorKind::*;
+        
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            UnknownMnemonic(name) => write!(f, "unknown mnemonic {}", name),
+            UnknownDirective(name) => write!(f, "unknown directive {}", name),
+            OperandCount(found, min, max) if min == max => write!(f, "expected {} operands, found {}", min, found),
+            OperandCount(found, min, max) => write!(f, "expected {} to {} operands, found {}", min, max, found),
+            BadOperand(operand) => write!(f, "bad operand {}", operand),
+            UndefinedSymbol(name) => write!(f, "undefined symbol {}", name),
+            DuplicateLabel(name) => write!(f, "duplicate label {}", name),
+            OutOfRange(operand) => write!(f, "{} is out of range", operand),
+            Misaligned(addr) => write!(f, "instruction at {:#010X} is not word aligned", addr),
+            Encode(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// The output of [`assemble`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct Assembled {
+    pub vaddr: u32,
+    pub bytes: Vec<u8>,
+    pub symbols: HashMap<String, u32>,
+}
+
+impl Assembled {
+    /// Disassembles the output again, mapped at the address it was assembled for.
+    pub fn disassembly(&self, target: Target) -> Disassembly {
+        Disassembly::from_u8_target(&self.bytes, target).at_vaddr(self.vaddr)
+    }
+}
+
+/// Maps `MFC2`, `BC3T`, `LWC2` etc. to the generic coprocessor operation and number. The encoder rejects numbers
+/// that have their own operations or opcodes (e.g. `LWC0`).
+fn coprocessor_op(mnemonic: &str) -> Option<(Operation, u32)> {
+    use Operation::*;
+    
+    let z_at = if mnemonic.starts_with("BC") { 2 } else { mnemonic.len().checked_sub(1)? };
+    let z = mnemonic.get(z_at..(z_at + 1))?.parse::<u32>().ok().filter(|z| *z < 4)?;
+    
+    let op = match (&mnemonic[..z_at], &mnemonic[(z_at + 1)..]) {
+        ("MFC", "") => MFCz,
+        ("MTC", "") => MTCz,
+        ("CFC", "") => CFCz,
+        ("CTC", "") => CTCz,
+        ("COP", "") => COPz,
+        ("LWC", "") => LWCz,
+        ("LDC", "") => LDCz,
+        ("SWC", "") => SWCz,
+        ("SDC", "") => SDCz,
+        ("BC", "F") => BCzF,
+        ("BC", "FL") => BCzFL,
+        ("BC", "T") => BCzT,
+        ("BC", "TL") => BCzTL,
+        _ => return None
+    };
+    
+    Some((op, z))
+}
+
+/// Parses `token` as a register number following a case-insensitive prefix, e.g. `r12` or `FCR31`.
+fn number_after(token: &str, prefix: &str) -> Option<u8> {
+    let rest = token.get(..prefix.len()).filter(|start| start.eq_ignore_ascii_case(prefix)).and(token.get(prefix.len()..))?;
+    
+    if rest.is_empty() || !rest.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    rest.parse().ok()
+}
+
+fn name_index(names: &[&str], name: &str) -> Option<u8> {
+    names.iter().position(|known| known.eq_ignore_ascii_case(name)).map(|i| i as u8)
+}
+
+/// Accepts our compact names, the o32 names and numeric names (`$4`, `r4`). The n32 names are not accepted, as they
+/// give different numbers to `t0`-`t3`.
+fn parse_gpr(token: &str) -> Option<u8> {
+    let name = token.strip_prefix('$').unwrap_or(token);
+    
+    name_index(&CPU_REG_NAMES, name)
+        .or_else(|| name_index(&O32_REG_NAMES, name))
+        .or_else(|| number_after(token, "$"))
+        .or_else(|| number_after(name, "r"))
+        .filter(|reg| *reg < 32)
+}
+
+fn parse_cp0(token: &str, names: &[&str]) -> Option<u8> {
+    let name = token.strip_prefix('$').unwrap_or(token);
+    
+    name_index(names, name)
+        .or_else(|| number_after(token, "$"))
+        .or_else(|| number_after(name, "c0r"))
+        .filter(|reg| (*reg as usize) < names.len())
+}
+
+fn parse_fpr(token: &str) -> Option<u8> {
+    number_after(token.strip_prefix('$').unwrap_or(token), "f").filter(|reg| *reg < 32)
+}
+
+fn parse_fcr(token: &str) -> Option<u8> {
+    number_after(token.strip_prefix('$').unwrap_or(token), "fcr")
+        .or_else(|| number_after(token, "$"))
+        .filter(|reg| *reg < 32)
+}
+
+fn parse_cop(token: &str) -> Option<u8> {
+    number_after(token, "$").or_else(|| number_after(token, "cr")).filter(|reg| *reg < 32)
+}
+
+fn parse_vctrl(token: &str) -> Option<u8> {
+    name_index(&VU_CTRL_REG_NAMES, token.strip_prefix('$').unwrap_or(token))
+        .or_else(|| number_after(token, "$").filter(|reg| *reg < 3))
+}
+
+/// Accepts the accumulator names and `$8`-`$10`, as VSAR numbers them.
+fn parse_vacc(token: &str) -> Option<u8> {
+    name_index(&VU_ACC_NAMES, token.strip_prefix('$').unwrap_or(token))
+        .or_else(|| number_after(token, "$").filter(|acc| (8..11).contains(acc)).map(|acc| acc - 8))
+}
+
+/// Splits a vector register into its number and any `[...]` suffix.
+fn parse_vreg(token: &str) -> Option<(u8, &str)> {
+    let name = token.strip_prefix('$').unwrap_or(token);
+    let (reg, suffix) = name.split_at(name.find('[').unwrap_or(name.len()));
+    
+    number_after(reg.trim_end(), "v").filter(|reg| *reg < 32).map(|reg| (reg, suffix))
+}
+
+/// Inverse of [`crate::rsp::element_suffix`].
+fn parse_element(suffix: &str) -> Option<u8> {
+    if suffix.is_empty() {
+        return Some(0);
+    }
+    
+    let inner = suffix.strip_prefix('[')?.strip_suffix(']')?.trim();
+    
+    // The raw specifier, which is the only way to write 1
+    if let Some(e) = inner.strip_prefix(|c| c == 'e' || c == 'E') {
+        e.parse::<u8>().ok().filter(|e| *e < 16)
+    } else if let Some(n) = inner.strip_suffix(|c| c == 'q' || c == 'Q') {
+        n.parse::<u8>().ok().filter(|n| *n < 2).map(|n| n + 2)
+    } else if let Some(n) = inner.strip_suffix(|c| c == 'h' || c == 'H') {
+        n.parse::<u8>().ok().filter(|n| *n < 4).map(|n| n + 4)
+    } else {
+        inner.parse::<u8>().ok().filter(|n| *n < 8).map(|n| n + 8)
+    }
+}
+
+fn parse_lane(suffix: &str) -> Option<u8> {
+    if suffix.is_empty() {
+        return Some(0);
+    }
+    
+    suffix.strip_prefix('[')?.strip_suffix(']')?.trim().parse::<u8>().ok().filter(|lane| *lane < 32)
+}
+
+fn parse_number(text: &str) -> Option<u32> {
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if let Some(bin) = text.strip_prefix("0b").or_else(|| text.strip_prefix("0B")) {
+        u32::from_str_radix(bin, 2).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+fn is_symbol(name: &str) -> bool {
+    let mut chars = name.chars();
+    
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '.') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Evaluates an expression made of numbers, symbols, `%hi()`, `%lo()`, parentheses, `+` and `-`.
+fn eval(text: &str, symbols: &HashMap<String, u32>) -> Result<i64, AsmErrorKind> {
+    let mut rest = text.trim();
+    let value = eval_sum(&mut rest, symbols)?;
+    
+    match rest.trim() {
+        "" => Ok(value),
+        _ => Err(AsmErrorKind::BadOperand(text.to_string())),
+    }
+}
+
+fn eval_sum(rest: &mut &str, symbols: &HashMap<String, u32>) -> Result<i64, AsmErrorKind> {
+    let mut value = eval_term(rest, symbols)?;
+    
+    loop {
+        *rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix('+') {
+            *rest = after;
+            value += eval_term(rest, symbols)?;
+        } else if let Some(after) = rest.strip_prefix('-') {
+            *rest = after;
+            value -= eval_term(rest, symbols)?;
+        } else {
+            return Ok(value);
+        }
+    }
+}
+
+fn eval_term(rest: &mut &str, symbols: &HashMap<String, u32>) -> Result<i64, AsmErrorKind> {
+    *rest = rest.trim_start();
+    
+    if let Some(after) = rest.strip_prefix('-') {
+        *rest = after;
+        return Ok(-eval_term(rest, symbols)?);
+    }
+    if let Some(after) = rest.strip_prefix('+') {
+        *rest = after;
+        return eval_term(rest, symbols);
+    }
+    
+    for (prefix, relocation) in [("%hi(", true), ("%lo(", false), ("(", false)].iter() {
+        if let Some(after) = rest.strip_prefix(prefix) {
+            *rest = after;
+            let value = eval_sum(rest, symbols)?;
+            *rest = rest.trim_start().strip_prefix(')').ok_or_else(|| AsmErrorKind::BadOperand(format!("{}...", prefix)))?;
+            
+            return Ok(match (*prefix, relocation) {
+                // %hi is adjusted for the sign extension of the %lo that gets added to it.
+                (_, true) => ((value + 0x8000) >> 16) & 0xFFFF,
+                ("(", _) => value,
+                _ => (value as u16 as i16) as i64,
+            });
+        }
+    }
+    
+    let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.')).unwrap_or(rest.len());
+    let word = &rest[..len];
+    *rest = &rest[len..];
+    
+    match word.chars().next() {
+        Some(c) if c.is_ascii_digit() => parse_number(word).map(|value| value as i64).ok_or_else(|| AsmErrorKind::BadOperand(word.to_string())),
+        Some(_) if is_symbol(word) => symbols.get(word).map(|value| *value as i64).ok_or_else(|| AsmErrorKind::UndefinedSymbol(word.to_string())),
+        _ => Err(AsmErrorKind::BadOperand(word.to_string())),
+    }
+}
+
+/// Evaluates `text` and checks that the result lies within `min..=max`.
+fn eval_in(text: &str, min: i64, max: i64, symbols: &HashMap<String, u32>) -> Result<i64, AsmErrorKind> {
+    match eval(text, symbols)? {
+        value if (min..=max).contains(&value) => Ok(value),
+        _ => Err(AsmErrorKind::OutOfRange(text.to_string())),
+    }
+}
+
+fn parse_mem(token: &str, symbols: &HashMap<String, u32>) -> Result<Operand, AsmErrorKind> {
+    let bad = || AsmErrorKind::BadOperand(token.to_string());
+    
+    let open = token.rfind('(').ok_or_else(bad)?;
+    let base = token[(open + 1)..].strip_suffix(')').and_then(|base| parse_gpr(base.trim())).ok_or_else(bad)?;
+    let offset = match token[..open].trim() {
+        "" => 0,
+        offset => eval_in(offset, -0x8000, 0x7FFF, symbols)?,
+    };
+    
+    Ok(Operand::Mem(base, offset as i16))
+}
+
+fn parse_operand(slot: Slot, token: &str, symbols: &HashMap<String, u32>) -> Result<Operand, AsmErrorKind> {
+    use Operand::*;
+    
+    let bad = || AsmErrorKind::BadOperand(token.to_string());
+    let int = |min: i64, max: i64| eval_in(token, min, max, symbols);
+    
+    Ok(match slot {
+        Slot::Gpr(_) => Reg(parse_gpr(token).ok_or_else(bad)?),
+        Slot::Cp0 => Cp0Reg(parse_cp0(token, &CP0_REG_NAMES).ok_or_else(bad)?),
+        Slot::RspCp0 => RspCp0Reg(parse_cp0(token, &RSP_CP0_REG_NAMES).ok_or_else(bad)?),
+        Slot::Fpr(_) => Cp1Reg(parse_fpr(token).ok_or_else(bad)?),
+        Slot::Fcr => Cp1CtrlReg(parse_fcr(token).ok_or_else(bad)?),
+        Slot::Cop(_) => CopReg(parse_cop(token).ok_or_else(bad)?),
+        Slot::VReg(_) => match parse_vreg(token) {
+            Some((reg, "")) => VReg(reg),
+            _ => return Err(bad())
+        },
+        Slot::VElement => {
+            let (reg, suffix) = parse_vreg(token).ok_or_else(bad)?;
+            VRegElement(reg, parse_element(suffix).ok_or_else(bad)?)
+        },
+        Slot::VLaneDest | Slot::VLaneMove | Slot::VLaneMem => {
+            let (reg, suffix) = parse_vreg(token).ok_or_else(bad)?;
+            VRegLane(reg, parse_lane(suffix).ok_or_else(bad)?)
+        },
+        Slot::VCtrl => VCtrlReg(parse_vctrl(token).ok_or_else(bad)?),
+        Slot::VAcc => VAcc(parse_vacc(token).ok_or_else(bad)?),
+        Slot::Shift => Lit8(int(0, 0xFF)? as u8),
+        Slot::Imm16 => Imm16(int(-0x8000, 0x7FFF)? as i16),
+        Slot::Lit16 => Lit16(int(-0x8000, 0xFFFF)? as u16),
+        // A signed offset is relative, as printed for unresolved branches. Anything else is an address or label.
+        Slot::Branch if token.starts_with(['+', '-']) => match int(-0x20000, 0x1FFFC)? {
+            offset if offset % 4 == 0 => Branch((offset / 4) as i16),
+            _ => return Err(AsmErrorKind::OutOfRange(token.to_string()))
+        },
+        Slot::Branch | Slot::Jump => Addr(int(i32::MIN as i64, u32::MAX as i64)? as u32),
+        Slot::Mem | Slot::VMem(_) => parse_mem(token, symbols)?,
+        Slot::CacheOp => match (0..32).find(|op| cache_op_name(*op).is_some_and(|name| name.eq_ignore_ascii_case(token))) {
+            Some(op) => CacheOp(op),
+            None => CacheOp(int(0, 0xFF)? as u8),
+        },
+        Slot::Code20 | Slot::BreakCode | Slot::Code10 | Slot::Stype => Code(int(0, u32::MAX as i64)? as u32),
+        Slot::Cofun => Lit32(int(0, u32::MAX as i64)? as u32),
+    })
+}
+
+/// Assembles a single (non pseudo) instruction at `pc`.
+fn assemble_instruction(mnemonic: &str, operands: &[String], pc: u32, target: Target, symbols: &HashMap<String, u32>) -> Result<u32, AsmErrorKind> {
+    let upper = mnemonic.to_uppercase();
+    
+    let (op, z) = match Operation::from_str(&upper) {
+        Ok(op) if layout(op, target).is_some() => (op, 0),
+        _ => coprocessor_op(&upper).filter(|_| target == Target::VR4300).ok_or_else(|| AsmErrorKind::UnknownMnemonic(mnemonic.to_string()))?,
+    };
+    let (_, slots) = layout(op, target).ok_or_else(|| AsmErrorKind::UnknownMnemonic(mnemonic.to_string()))?;
+    
+    let required = slots.iter().filter(|slot| !slot.optional()).count();
+    if operands.len() < required || operands.len() > slots.len() {
+        return Err(AsmErrorKind::OperandCount(operands.len(), required, slots.len()));
+    }
+    
+    // The coprocessor number of generic operations is passed to the encoder through the opcode bits.
+    let mut instr = Instruction::new0(z << 26, op);
+    for ((slot, token), arg) in slots.iter().zip(operands).zip(instr.args.iter_mut()) {
+        *arg = Some(parse_operand(*slot, token, symbols)?);
+    }
+    
+    Ok(instr.encode_at(pc, target)?)
+}
+
+fn pseudo_arity(mnemonic: &str) -> Option<usize> {
+    match mnemonic {
+        "B" | "BAL" => Some(1),
+        "BEQZ" | "BEQZL" | "BNEZ" | "BNEZL" | "LA" | "LI" | "MOVE" | "NEG" | "NEGU" | "NOT" => Some(2),
+        _ => None
+    }
+}
+
+/// Expands pseudo-instructions into the real instructions [`crate::pseudo::recognize`] turns back into them.
+fn expand(mnemonic: &str, operands: Vec<String>) -> Result<Vec<(String, Vec<String>)>, AsmErrorKind> {
+    let upper = mnemonic.to_uppercase();
+    let instr = |mnemonic: &str, operands: &[&str]| (mnemonic.to_string(), operands.iter().map(|operand| operand.to_string()).collect());
+    let ops: Vec<&str> = operands.iter().map(String::as_str).collect();
+    
+    Ok(match (upper.as_str(), ops.as_slice()) {
+        ("MOVE", [rd, rs]) => vec![instr("ADDU", &[rd, rs, "zero"])],
+        ("NOT", [rd, rs]) => vec![instr("NOR", &[rd, rs, "zero"])],
+        ("NEG", [rd, rt]) => vec![instr("SUB", &[rd, "zero", rt])],
+        ("NEGU", [rd, rt]) => vec![instr("SUBU", &[rd, "zero", rt])],
+        
+        ("B", [dest]) => vec![instr("BEQ", &["zero", "zero", dest])],
+        ("BAL", [dest]) => vec![instr("BGEZAL", &["zero", dest])],
+        ("BEQZ", [rs, dest]) => vec![instr("BEQ", &[rs, "zero", dest])],
+        ("BEQZL", [rs, dest]) => vec![instr("BEQL", &[rs, "zero", dest])],
+        ("BNEZ", [rs, dest]) => vec![instr("BNE", &[rs, "zero", dest])],
+        ("BNEZL", [rs, dest]) => vec![instr("BNEL", &[rs, "zero", dest])],
+        
+        // The size of LI has to be known before labels are, so only literal values get the short forms.
+        ("LI", [rt, value]) => match eval(value, &HashMap::new()) {
+            Ok(imm) if (-0x8000..=0x7FFF).contains(&imm) => vec![instr("ADDIU", &[rt, "zero", value])],
+            Ok(imm) if (0..=0xFFFF).contains(&imm) => vec![instr("ORI", &[rt, "zero", value])],
+            Ok(imm) if (-0x80000000..=0xFFFFFFFF).contains(&imm) => vec![
+                instr("LUI", &[rt, &format!("{:#X}", (imm >> 16) & 0xFFFF)]),
+                instr("ORI", &[rt, rt, &format!("{:#X}", imm & 0xFFFF)]),
+            ],
+            Ok(_) => return Err(AsmErrorKind::OutOfRange(value.to_string())),
+            Err(_) => expand("LA", operands)?,
+        },
+        ("LA", [rt, addr]) => vec![
+            instr("LUI", &[rt, &format!("%hi({})", addr)]),
+            instr("ADDIU", &[rt, rt, &format!("%lo({})", addr)]),
+        ],
+        
+        // GNU as form, with an implied `ra`.
+        ("JALR", [rs]) => vec![instr("JALR", &["ra", rs])],
+        
+        (name, _) => match pseudo_arity(name) {
+            Some(arity) => return Err(AsmErrorKind::OperandCount(ops.len(), arity, arity)),
+            None => vec![(mnemonic.to_string(), operands)],
+        }
+    })
+}
+
+fn strip_comment(line: &str) -> &str {
+    let end = [line.find('#'), line.find(';'), line.find("//")].iter().flatten().min().copied();
+    
+    &line[..end.unwrap_or(line.len())]
+}
+
+fn matching_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' if depth == 1 => return Some(i),
+            ']' => depth -= 1,
+            _ => ()
+        }
+    }
+    
+    None
+}
+
+/// Unwraps the `[0x00000000][OP args]` form printed for instructions, skipping any leading address and raw word groups.
+fn unwrap_brackets(mut text: &str) -> &str {
+    while text.starts_with('[') {
+        let close = match matching_bracket(text) {
+            Some(close) => close,
+            None => return text,
+        };
+        
+        let inner = text[1..close].trim();
+        if parse_number(inner).is_none() {
+            return inner;
+        }
+        text = text[(close + 1)..].trim_start();
+    }
+    
+    text
+}
+
+fn split_label(text: &str) -> Option<(&str, &str)> {
+    let colon = text.find(':')?;
+    let label = text[..colon].trim();
+    
+    match is_symbol(label) {
+        true => Some((label, text[(colon + 1)..].trim())),
+        false => None,
+    }
+}
+
+/// Splits operands on commas that aren't inside parentheses or brackets.
+fn split_operands(text: &str) -> Vec<String> {
+    let mut operands = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                operands.push(text[start..i].trim().to_string());
+                start = i + 1;
+            },
+            _ => ()
+        }
+    }
+    
+    let last = text[start..].trim();
+    if !last.is_empty() || !operands.is_empty() {
+        operands.push(last.to_string());
+    }
+    
+    operands
+}
+
+enum Statement {
+    Instruction(String, Vec<String>),
+    /// Values of the given width in bytes.
+    Data(usize, Vec<String>),
+    Padding(usize),
+}
+
+pub fn assemble(source: &str, vaddr: u32) -> Result<Assembled, AsmError> {
+    assemble_target(source, vaddr, Target::VR4300)
+}
+
+/// Assembles `source` for the target, with the first byte placed at `vaddr`.
+/// 
+/// Each line may hold labels (`loop:`), then an instruction or directive, then a comment (`#`, `;` or `//`).
+/// Instructions are accepted both as printed by [`Instruction`]'s `Display` (including any leading address and raw
+/// word columns) and in GNU as syntax. Branch operands starting with a sign are offsets in bytes from the delay slot;
+/// any other branch or jump operand is an absolute address, in which `.` stands for the address of the statement. The
+/// supported directives are `.word`, `.half`, `.byte` and `.align n` (to a `2^n` boundary).
+pub fn assemble_target(source: &str, vaddr: u32, target: Target) -> Result<Assembled, AsmError> {
+    let mut symbols = HashMap::new();
+    let mut statements = Vec::new();
+    let mut offset = 0u32;
+    
+    // First pass: lay out every statement and collect the labels.
+    for (i, line) in source.lines().enumerate() {
+        let err = |kind| AsmError { line: i + 1, kind };
+        
+        let mut text = unwrap_brackets(strip_comment(line).trim());
+        while let Some((label, rest)) = split_label(text) {
+            if symbols.insert(label.to_string(), vaddr.wrapping_add(offset)).is_some() {
+                return Err(err(AsmErrorKind::DuplicateLabel(label.to_string())));
+            }
+            text = rest;
+        }
+        
+        if text.is_empty() {
+            continue;
+        }
+        
+        let (mnemonic, operands) = match text.find(char::is_whitespace) {
+            Some(split) => (&text[..split], split_operands(&text[split..])),
+            None => (text, Vec::new()),
+        };
+        
+        if mnemonic.starts_with('.') {
+            let width = match mnemonic.to_lowercase().as_str() {
+                ".word" => 4,
+                ".half" => 2,
+                ".byte" => 1,
+                ".align" => {
+                    let power = match operands.as_slice() {
+                        [power] => eval_in(power, 0, 16, &HashMap::new()).map_err(err)?,
+                        _ => return Err(err(AsmErrorKind::OperandCount(operands.len(), 1, 1))),
+                    };
+                    let align = 1u32 << power;
+                    let padding = (align - offset % align) % align;
+                    
+                    statements.push((i + 1, offset, Statement::Padding(padding as usize)));
+                    offset += padding;
+                    continue;
+                },
+                ".set" | ".text" | ".globl" | ".global" => continue,
+                _ => return Err(err(AsmErrorKind::UnknownDirective(mnemonic.to_string()))),
+            };
+            
+            let len = (width * operands.len()) as u32;
+            statements.push((i + 1, offset, Statement::Data(width, operands)));
+            offset += len;
+            continue;
+        }
+        
+        for (mnemonic, operands) in expand(mnemonic, operands).map_err(err)? {
+            if !offset.is_multiple_of(4) {
+                return Err(err(AsmErrorKind::Misaligned(vaddr.wrapping_add(offset))));
+            }
+            
+            statements.push((i + 1, offset, Statement::Instruction(mnemonic, operands)));
+            offset += 4;
+        }
+    }
+    
+    // Second pass: encode, now that every label is known.
+    let mut bytes = Vec::with_capacity(offset as usize);
+    for (line, offset, statement) in statements {
+        let err = |kind| AsmError { line, kind };
+        symbols.insert(".".to_string(), vaddr.wrapping_add(offset));
+        
+        match statement {
+            Statement::Instruction(mnemonic, operands) => {
+                let code = assemble_instruction(&mnemonic, &operands, vaddr.wrapping_add(offset), target, &symbols).map_err(err)?;
+                bytes.extend_from_slice(&code.to_be_bytes());
+            },
+            Statement::Data(width, values) => for value in values {
+                let min = -(1i64 << (width * 8 - 1));
+                let max = (1i64 << (width * 8)) - 1;
+                let value = eval_in(&value, min, max, &symbols).map_err(err)? as u32;
+                
+                bytes.extend_from_slice(&value.to_be_bytes()[(4 - width)..]);
+            },
+            Statement::Padding(len) => bytes.resize(bytes.len() + len, 0),
+        }
+    }
+    
+    symbols.remove(".");
+    Ok(Assembled { vaddr, bytes, symbols })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assemble, assemble_target, AsmErrorKind};
+    use crate::disassembly::{disassemble_target, tests::ENCODINGS, Disassembly, Operation, Operation::*, Target};
+    
+    /// The generic coprocessor operations don't print their coprocessor number, so can't be assembled from it.
+    fn prints_coprocessor(op: Operation) -> bool {
+        !matches!(op, MFCz | MTCz | CFCz | CTCz | COPz | LWCz | LDCz | SWCz | SDCz | BCzF | BCzFL | BCzT | BCzTL | Unknown)
+    }
+    
+    fn words(bytes: &[u8]) -> Vec<u32> {
+        bytes.chunks(4).map(|word| u32::from_be_bytes([word[0], word[1], word[2], word[3]])).collect()
+    }
+    
+    #[test]
+    fn display_output_round_trips() {
+        for (target, code, op, _) in ENCODINGS.iter().filter(|row| prints_coprocessor(row.2)) {
+            let text = disassemble_target(*code, *target).to_string();
+            let assembled = assemble_target(&text, 0, *target).unwrap_or_else(|err| panic!("{}: {}", text, err));
+            
+            assert_eq!(words(&assembled.bytes), vec![*code], "{} ({})", text, op);
+        }
+    }
+    
+    #[test]
+    fn resolved_display_output_round_trips() {
+        let raw: Vec<u32> = ENCODINGS.iter().filter(|row| row.0 == Target::VR4300 && prints_coprocessor(row.2)).map(|row| row.1).collect();
+        let disasm = Disassembly::from_u32(&raw).at_vaddr(0x80000400);
+        
+        let text: String = disasm.instructions.iter().enumerate().map(|(i, instr)| format!("[{:#010X}]{}\n", i * 4, instr)).collect();
+        let assembled = assemble(&text, 0x80000400).unwrap();
+        
+        assert!(Disassembly::from_u8(&assembled.bytes) == disasm);
+    }
+    
+    #[test]
+    fn gnu_syntax() {
+        let source = "
+            .set noreorder
+            start:
+                lui     $t0, %hi(data)      # comment
+                addiu   $t0, $t0, %lo(data)
+                lw      $v0, 4($t0)
+                beqz    $v0, start
+                nop
+                jal     func
+                mfc0    $t1, $12
+                mtc2    $t1, $5
+                bc1t    .Lend
+                li      $a0, 0x12345678
+            .Lend:
+                jr      $ra
+            func:
+                jalr    $t9
+            data:
+                .half   0x1234
+                .byte   1, -1, 2
+                .align  3
+                .word   data, func - start
+        ";
+        
+        let assembled = assemble(source, 0x80000400).unwrap();
+        
+        assert_eq!(words(&assembled.bytes), vec![
+            0x3C088000, 0x25080434, 0x8D020004, 0x1040FFFC, 0x00000000, 0x0C00010C, 0x40096000, 0x48892800,
+            0x45010002, 0x3C041234, 0x34845678, 0x03E00008, 0x0320F809, 0x123401FF, 0x02000000, 0x00000000,
+            0x80000434, 0x00000030,
+        ]);
+        assert_eq!(assembled.symbols["data"], 0x80000434);
+        assert_eq!(assembled.disassembly(Target::VR4300).vaddr, Some(0x80000400));
+    }
+    
+    #[test]
+    fn break_codes() {
+        // A single code goes in bits 25..16, as with GNU as
+        assert_eq!(words(&assemble("break", 0).unwrap().bytes), vec![0x0000000D]);
+        assert_eq!(words(&assemble("break 7", 0).unwrap().bytes), vec![0x0007000D]);
+        assert_eq!(words(&assemble("break 1, 2", 0).unwrap().bytes), vec![0x0001008D]);
+        assert_eq!(words(&assemble("syscall 7", 0).unwrap().bytes), vec![0x000001CC]);
+        assert!(assemble("break 0x400", 0).is_err());
+    }
+    
+    #[test]
+    fn rsp_syntax() {
+        let source = "
+            vmudh   $v1, $v2, $v3[1q]
+            vrcp    $v4[1], $v5[4]
+            vadd    $v30, $v1, $v2[e1]
+            lqv     $v6[0], 0x20(s0)
+            mfc0    t0, SP_STATUS
+            loop: bne t0, zr, loop
+        ";
+        
+        let assembled = assemble_target(source, 0x04001000, Target::RSP).unwrap();
+        
+        assert_eq!(words(&assembled.bytes), vec![0x4A631047, 0x4B850930, 0x4A220F90, 0xCA062002, 0x40082000, 0x1500FFFF]);
+    }
+    
+    #[test]
+    fn errors() {
+        let err = |source: &str| assemble(source, 0).unwrap_err();
+        
+        assert_eq!(err("nop\nfoo t0").line, 2);
+        assert_eq!(err("foo t0").kind, AsmErrorKind::UnknownMnemonic(String::from("foo")));
+        assert_eq!(err("addu t0, t1").kind, AsmErrorKind::OperandCount(2, 3, 3));
+        assert_eq!(err("addiu t0, t1, 0x8000").kind, AsmErrorKind::OutOfRange(String::from("0x8000")));
+        assert_eq!(err("b nowhere").kind, AsmErrorKind::UndefinedSymbol(String::from("nowhere")));
+        assert_eq!(err("a: nop\na: nop").kind, AsmErrorKind::DuplicateLabel(String::from("a")));
+        assert_eq!(err(".byte 1\nnop").kind, AsmErrorKind::Misaligned(1));
+        assert_eq!(err("lw t0, 0(t10)").kind, AsmErrorKind::BadOperand(String::from("0(t10)")));
+        assert_eq!(err(".org 0").kind, AsmErrorKind::UnknownDirective(String::from(".org")));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::checksum::{CHECKSUM_LENGTH, CHECKSUM_START};
+use crate::cic::CicFamily;
+use crate::disassembly::{Disassembly, Target};
+use crate::rom::Rom;
+
+/// ROM offset of the boot segment, which the IPL3 copies to RAM before jumping to it. It's the same area the header
+/// CRCs cover.
+pub const BOOT_SEGMENT_START: usize = CHECKSUM_START;
+/// Size of the boot segment.
+pub const BOOT_SEGMENT_LENGTH: usize = CHECKSUM_LENGTH;
+
+impl CicFamily {
+    /// Where the IPL3 actually loads the boot segment and jumps to, given the entry point in the header. The 6103 and
+    /// 6106 bootcodes subtract a fixed offset, so their headers hold an address that's never used directly.
+    pub fn boot_address(self, pc: u32) -> u32 {
+        match self {
+            CicFamily::Cic6103 => pc.wrapping_sub(0x100000),
+            CicFamily::Cic6106 => pc.wrapping_sub(0x200000),
+            _ => pc,
+        }
+    }
+}
+
+impl Rom {
+    /// Load address of the boot segment, using the CIC family identified from the bootcode.
+    pub fn boot_address(&self) -> u32 {
+        self.cic().boot_address(self.header().pc)
+    }
+    
+    /// Disassembles the boot segment at its load address, using the CIC family identified from the bootcode. ROMs
+    /// smaller than 1 MiB past the bootcode give a shorter segment.
+    pub fn boot_segment(&self) -> Disassembly {
+        self.boot_segment_as(self.cic())
+    }
+    
+    pub fn boot_segment_as(&self, cic: CicFamily) -> Disassembly {
+        let vaddr = cic.boot_address(self.header().pc);
+        
+        Disassembly::from_rom(self.data(), BOOT_SEGMENT_START, BOOT_SEGMENT_LENGTH, vaddr, Target::VR4300)
+    }
+    
+    /// Disassembles the IPL3 bootcode where it runs, in RSP DMEM. Truncated ROMs give as much of it as they have.
+    pub fn bootcode_disassembly(&self) -> Disassembly {
+        Disassembly::from_rom(self.data(), 0x40, 0x1000 - 0x40, 0xA4000040, Target::VR4300)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cic::CicFamily;
+    use crate::disassembly::Operation;
+    use crate::rom::{test_rom, Rom};
+    
+    fn rom(len: usize) -> Rom {
+        test_rom(len).with_pc(0x80300400).with_code(0x1000, &[0x3C1D8040, 0x0C000104]).rom()
+    }
+    
+    #[test]
+    fn boot_addresses() {
+        assert_eq!(CicFamily::Cic6102.boot_address(0x80000400), 0x80000400);
+        assert_eq!(CicFamily::Cic6103.boot_address(0x80100400), 0x80000400);
+        assert_eq!(CicFamily::Cic6106.boot_address(0x80200400), 0x80000400);
+        assert_eq!(rom(0x2000).boot_address(), 0x80300400);
+    }
+    
+    #[test]
+    fn boot_segment() {
+        let rom = rom(0x200000);
+        
+        let disasm = rom.boot_segment_as(CicFamily::Cic6106);
+        assert_eq!(disasm.len(), 0x40000);
+        assert_eq!(disasm.vaddr, Some(0x80100400));
+        assert_eq!(disasm.rom_offset, Some(0x1000));
+        assert_eq!(disasm.get(0x80100400).map(|instr| instr.op), Some(Operation::LUI));
+        assert_eq!(disasm.destination(1), Some(0x80000410));
+        assert_eq!(disasm.vaddr_to_rom(0x80100404), Some(0x1004));
+        
+        assert_eq!(rom.boot_segment().vaddr, Some(0x80300400));
+        assert_eq!(Rom::lenient(rom.data()[..0x1802].to_vec()).0.boot_segment().len(), 0x200);
+        assert!(Rom::lenient(rom.data()[..0x1000].to_vec()).0.boot_segment().is_empty());
+    }
+    
+    #[test]
+    fn bootcode() {
+        let rom = test_rom(0x1000).with_code(0x40, &[0x40086800]).rom();
+        
+        let disasm = rom.bootcode_disassembly();
+        assert_eq!(disasm.len(), 0x3F0);
+        assert_eq!(disasm.get(0xA4000040).map(|instr| instr.op), Some(Operation::MFC0));
+        
+        assert_eq!(Rom::lenient(rom.data()[..0x4A].to_vec()).0.bootcode_disassembly().len(), 2);
+        assert!(Rom::lenient(rom.data()[..8].to_vec()).0.bootcode_disassembly().is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use crate::cic::CicFamily;
+use crate::rom::Rom;
+
+/// Start of the checksummed area, right after the bootcode.
+pub const CHECKSUM_START: usize = 0x1000;
+/// Size of the checksummed area.
+pub const CHECKSUM_LENGTH: usize = 0x100000;
+
+/// Start of the table that the 6105 bootcode mixes into the checksum.
+const CIC6105_TABLE: usize = 0x750;
+
+fn seed(cic: CicFamily) -> Option<u32> {
+    match cic {
+        CicFamily::Cic6101 | CicFamily::Cic6102 => Some(0xF8CA4DDC),
+        CicFamily::Cic6103 => Some(0xA3886759),
+        CicFamily::Cic6105 => Some(0xDF26F436),
+        CicFamily::Cic6106 => Some(0x1FEA617A),
+        _ => None
+    }
+}
+
+fn word(data: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0u8; 4];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = data.get(offset + i).copied().unwrap_or(0);
+    }
+    
+    u32::from_be_bytes(bytes)
+}
+
+/// Computes CRC1 and CRC2 the way the IPL3 of the given CIC family does, over a big-endian ROM. Bytes past the end of a
+/// short ROM are read as zero.
+/// 
+/// Returns `None` for families whose bootcode doesn't use this checksum (8303, iQue, libdragon and unknown).
+pub fn checksum(data: &[u8], cic: CicFamily) -> Option<(u32, u32)> {
+    let seed = seed(cic)?;
+    let (mut t1, mut t2, mut t3, mut t4, mut t5, mut t6) = (seed, seed, seed, seed, seed, seed);
+    
+    for offset in (CHECKSUM_START..(CHECKSUM_START + CHECKSUM_LENGTH)).step_by(4) {
+        let d = word(data, offset);
+        
+        if t6.wrapping_add(d) < t6 {
+            t4 = t4.wrapping_add(1);
+        }
+        t6 = t6.wrapping_add(d);
+        t3 ^= d;
+        
+        let r = d.rotate_left(d & 0x1F);
+        t5 = t5.wrapping_add(r);
+        t2 ^= if t2 > d { r } else { t6 ^ d };
+        
+        t1 = match cic {
+            CicFamily::Cic6105 => t1.wrapping_add(word(data, CIC6105_TABLE + (offset & 0xFF)) ^ d),
+            _ => t1.wrapping_add(t5 ^ d),
+        };
+    }
+    
+    Some(match cic {
+        CicFamily::Cic6103 => ((t6 ^ t4).wrapping_add(t3), (t5 ^ t2).wrapping_add(t1)),
+        CicFamily::Cic6106 => (t6.wrapping_mul(t4).wrapping_add(t3), t5.wrapping_mul(t2).wrapping_add(t1)),
+        _ => (t6 ^ t4 ^ t3, t5 ^ t2 ^ t1),
+    })
+}
+
+/// Result of checking the CRCs stored in a header.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct CrcCheck {
+    pub cic: CicFamily,
+    pub stored: (u32, u32),
+    /// `None` when the CIC family doesn't use the standard checksum.
+    pub computed: Option<(u32, u32)>,
+}
+
+impl CrcCheck {
+    pub fn is_valid(&self) -> bool {
+        self.computed == Some(self.stored)
+    }
+}
+
+impl Rom {
+    /// Computes CRC1 and CRC2 using the CIC family identified from the bootcode.
+    pub fn checksum(&self) -> Option<(u32, u32)> {
+        checksum(self.data(), self.cic())
+    }
+    
+    /// Compares the CRCs in the header against the ones computed for `cic`.
+    pub fn verify_crc_as(&self, cic: CicFamily) -> CrcCheck {
+        CrcCheck {
+            cic,
+            stored: (self.header().crc1, self.header().crc2),
+            computed: checksum(self.data(), cic),
+        }
+    }
+    
+    /// Compares the CRCs in the header against the ones computed using the CIC family identified from the bootcode.
+    pub fn verify_crc(&self) -> CrcCheck {
+        self.verify_crc_as(self.cic())
+    }
+    
+    /// Writes the CRCs computed for `cic` into both the header and the image. Returns them, or `None` if the family
+    /// doesn't use the standard checksum, in which case nothing is changed.
+    pub fn fix_crc_as(&mut self, cic: CicFamily) -> Option<(u32, u32)> {
+        let (crc1, crc2) = checksum(self.data(), cic)?;
+        
+        self.edit_header(|header| {
+            header.crc1 = crc1;
+            header.crc2 = crc2;
+        });
+        
+        Some((crc1, crc2))
+    }
+    
+    /// Like [`Rom::fix_crc_as`], using the CIC family identified from the bootcode.
+    pub fn fix_crc(&mut self) -> Option<(u32, u32)> {
+        self.fix_crc_as(self.cic())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::checksum;
+    use crate::cic::CicFamily;
+    use crate::rom::{test_rom, Rom};
+    
+    /// A ROM of 1 MiB + 4 KiB filled with a simple pattern. The expected values below were computed with a separate
+    /// implementation of `n64crc.c`.
+    fn image() -> Vec<u8> {
+        test_rom(0x101000).with_fill(|i| (i.wrapping_mul(2654435761) >> 13) as u8).into_bytes()
+    }
+    
+    #[test]
+    fn known_values() {
+        let bytes = image();
+        
+        assert_eq!(checksum(&bytes, CicFamily::Cic6102), Some((0xF5CE50DC, 0x432FD320)));
+        assert_eq!(checksum(&bytes, CicFamily::Cic6101), checksum(&bytes, CicFamily::Cic6102));
+        assert_eq!(checksum(&bytes, CicFamily::Cic6103), Some((0xA6947459, 0xA2687D91)));
+        assert_eq!(checksum(&bytes, CicFamily::Cic6105), Some((0xDC2AF736, 0x480FA2E6)));
+        assert_eq!(checksum(&bytes, CicFamily::Cic6106), Some((0x5B347D9E, 0x8ABEE6B1)));
+        assert_eq!(checksum(&bytes, CicFamily::Cic8303), None);
+        assert_eq!(checksum(&bytes, CicFamily::Unknown), None);
+        
+        assert_eq!(checksum(&bytes[..0x1000], CicFamily::Cic6102), checksum(&[&bytes[..0x1000], &[0u8; 0x100000][..]].concat(), CicFamily::Cic6102));
+    }
+    
+    #[test]
+    fn verify_and_fix() {
+        let mut rom = Rom::new(image()).unwrap();
+        
+        let check = rom.verify_crc_as(CicFamily::Cic6105);
+        assert!(!check.is_valid());
+        assert_eq!(check.computed, checksum(&image(), CicFamily::Cic6105));
+        
+        assert_eq!(rom.fix_crc_as(CicFamily::Cic6105), check.computed);
+        assert!(rom.verify_crc_as(CicFamily::Cic6105).is_valid());
+        assert!(!rom.verify_crc_as(CicFamily::Cic6102).is_valid());
+        
+        let reparsed = Rom::new(rom.data().to_vec()).unwrap();
+        assert_eq!((reparsed.header().crc1, reparsed.header().crc2), check.computed.unwrap());
+        
+        assert_eq!(rom.fix_crc_as(CicFamily::Libdragon), None);
+        assert!(!rom.verify_crc_as(CicFamily::Libdragon).is_valid());
+        assert!(rom.verify_crc_as(CicFamily::Cic6105).is_valid());
+    }
+}
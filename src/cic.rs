@@ -0,0 +1,167 @@
+use crate::rom::Rom;
+use crate::table::{parse_table, TableError};
+use std::path::Path;
+use std::str::FromStr;
+
+/// The built-in table, see [`CicTable::builtin`].
+const BUILTIN: &str = include_str!("../data/cic.txt");
+
+/// Banner placed in the bootcode by libdragon's open-source IPL3. Its hash changes with every build, so it is found by
+/// this instead.
+const LIBDRAGON_BANNER: &[u8] = b"Libdragon IPL3";
+
+/// Family of the CIC lockout chip a bootcode was written for. NTSC and PAL chips of the same family share a bootcode,
+/// aside from the 6101 and 7102.
+#[derive(Debug, PartialEq, Eq, Hash, strum_macros::Display, strum_macros::EnumString, Copy, Clone)]
+pub enum CicFamily {
+    #[strum(serialize = "6101")]
+    Cic6101,
+    #[strum(serialize = "6102/7101")]
+    Cic6102,
+    #[strum(serialize = "6103/7103")]
+    Cic6103,
+    #[strum(serialize = "6105/7105")]
+    Cic6105,
+    #[strum(serialize = "6106/7106")]
+    Cic6106,
+    /// The 64DD.
+    #[strum(serialize = "8303")]
+    Cic8303,
+    /// The iQue Player. The built-in table has no iQue bootcodes, so only a user-supplied table can map a hash to it.
+    #[strum(serialize = "iQue")]
+    IQue,
+    #[strum(serialize = "libdragon")]
+    Libdragon,
+    #[strum(serialize = "unknown")]
+    Unknown,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CicEntry {
+    /// CRC32 of the bootcode.
+    pub hash: u32,
+    pub family: CicFamily,
+    /// Name of the specific chip, e.g. `CIC-NUS-7102`.
+    pub name: String,
+}
+
+/// Maps bootcode hashes to CIC families, read from tables with one `<crc32> <family> <name>` entry per line.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CicTable {
+    pub entries: Vec<CicEntry>,
+}
+
+impl CicTable {
+    /// The table bundled from `data/cic.txt`.
+    pub fn builtin() -> CicTable {
+        CicTable::parse(BUILTIN).expect("built-in CIC table is invalid")
+    }
+    
+    pub fn parse(text: &str) -> Result<CicTable, TableError> {
+        let entries = parse_table(text, "<crc32> <family> <name>", |fields| {
+            let hash = u32::from_str_radix(fields.first()?.trim_start_matches("0x"), 16).ok()?;
+            let family = CicFamily::from_str(fields.get(1)?).ok()?;
+            
+            Some(CicEntry { hash, family, name: fields[2..].join(" ") })
+        })?;
+        
+        Ok(CicTable { entries })
+    }
+    
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<CicTable, TableError> {
+        CicTable::parse(&std::fs::read_to_string(path)?)
+    }
+    
+    /// Adds the entries of `other`. A later entry for a hash overrides an earlier one.
+    pub fn extend(&mut self, other: CicTable) {
+        self.entries.extend(other.entries);
+    }
+    
+    pub fn lookup(&self, hash: u32) -> Option<&CicEntry> {
+        self.entries.iter().rev().find(|entry| entry.hash == hash)
+    }
+    
+    /// Identifies the bootcode (ROM 0x40..0x1000).
+    pub fn identify(&self, bootcode: &[u8]) -> CicFamily {
+        if let Some(entry) = self.lookup(crc32fast::hash(bootcode)) {
+            return entry.family;
+        }
+        
+        match bootcode.windows(LIBDRAGON_BANNER.len()).any(|window| window == LIBDRAGON_BANNER) {
+            true => CicFamily::Libdragon,
+            false => CicFamily::Unknown,
+        }
+    }
+}
+
+impl Rom {
+    pub fn bootcode_bytes(&self) -> Vec<u8> {
+        self.bootcode().iter().flat_map(|word| word.to_be_bytes()).collect()
+    }
+    
+    /// CRC32 of the bootcode, which is what [`CicTable`] is keyed on.
+    pub fn bootcode_hash(&self) -> u32 {
+        crc32fast::hash(&self.bootcode_bytes())
+    }
+    
+    /// Identifies the CIC family using the built-in table.
+    pub fn cic(&self) -> CicFamily {
+        self.cic_with(&CicTable::builtin())
+    }
+    
+    pub fn cic_with(&self, table: &CicTable) -> CicFamily {
+        table.identify(&self.bootcode_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CicFamily, CicTable};
+    use crate::rom::{test_rom, Rom};
+    use crate::table::TableError;
+    
+    fn rom(bootcode: &[u8]) -> Rom {
+        test_rom(0x1000).with_bytes(0x40, bootcode).rom()
+    }
+    
+    #[test]
+    fn builtin_table() {
+        let table = CicTable::builtin();
+        
+        assert_eq!(table.lookup(0x90BB6CB5).map(|entry| entry.family), Some(CicFamily::Cic6102));
+        assert_eq!(table.lookup(0x009E9EA3).map(|entry| entry.name.as_str()), Some("CIC-NUS-7102"));
+        assert_eq!(table.lookup(0), None);
+        
+        for entry in &table.entries {
+            assert_eq!(entry.family.to_string().parse::<CicFamily>().unwrap(), entry.family);
+            assert_ne!(entry.family, CicFamily::IQue);
+        }
+    }
+    
+    #[test]
+    fn identification() {
+        let blank = rom(&[]);
+        assert_eq!(blank.bootcode_hash(), crc32fast::hash(&[0u8; 0xFC0]));
+        assert_eq!(blank.cic(), CicFamily::Unknown);
+        
+        let mut table = CicTable::builtin();
+        table.extend(CicTable::parse(&format!("{:08X} iQue iQue Player  # test entry\n", blank.bootcode_hash())).unwrap());
+        assert_eq!(blank.cic_with(&table), CicFamily::IQue);
+        assert_eq!(table.lookup(blank.bootcode_hash()).unwrap().name, "iQue Player");
+        
+        let mut bootcode = vec![0x24u8; 0x100];
+        bootcode[0x20..0x30].copy_from_slice(b"Libdragon IPL3  ");
+        assert_eq!(rom(&bootcode).cic(), CicFamily::Libdragon);
+    }
+    
+    #[test]
+    fn overrides_and_errors() {
+        let mut table = CicTable::parse("0x00000001 6101 A\n").unwrap();
+        table.extend(CicTable::parse("00000001 6106/7106 B").unwrap());
+        assert_eq!(table.lookup(1).unwrap().family, CicFamily::Cic6106);
+        
+        assert!(matches!(CicTable::parse("# comment\n\nXYZ 6101"), Err(TableError::Syntax(3, _, _))));
+        assert!(matches!(CicTable::parse("00000001 6104"), Err(TableError::Syntax(1, _, _))));
+        assert!(matches!(CicTable::parse("00000001"), Err(TableError::Syntax(1, _, _))));
+    }
+}
@@ -0,0 +1,461 @@
+use crate::hash::{from_hex, Hashes};
+use crate::rom::Rom;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// Dump status of a DAT entry.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum DumpStatus {
+    Good,
+    /// Checked against multiple carts.
+    Verified,
+    /// Known to be a bad dump.
+    BadDump,
+    /// No dump of this ROM exists; the entry has no hashes.
+    NoDump,
+}
+
+impl DumpStatus {
+    fn parse(text: &str) -> DumpStatus {
+        match text {
+            "verified" => DumpStatus::Verified,
+            "baddump" => DumpStatus::BadDump,
+            "nodump" => DumpStatus::NoDump,
+            _ => DumpStatus::Good,
+        }
+    }
+}
+
+/// A single `rom` of a DAT file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DatEntry {
+    /// Canonical title, e.g. `Super Mario 64 (USA)`.
+    pub game: String,
+    /// File name of the ROM, e.g. `Super Mario 64 (USA).z64`.
+    pub name: String,
+    pub size: Option<usize>,
+    pub crc32: Option<u32>,
+    pub md5: Option<[u8; 16]>,
+    pub sha1: Option<[u8; 20]>,
+    pub status: DumpStatus,
+}
+
+impl DatEntry {
+    /// Compares against the strongest hash both sides have. Entries without any hashes never match.
+    pub fn matches(&self, hashes: &Hashes) -> bool {
+        if self.size.is_some_and(|size| size != hashes.size) {
+            return false;
+        }
+        
+        match (self.sha1, self.md5, self.crc32) {
+            (Some(sha1), _, _) => sha1 == hashes.sha1,
+            (None, Some(md5), _) => md5 == hashes.md5,
+            (None, None, Some(crc32)) => crc32 == hashes.crc32,
+            (None, None, None) => false,
+        }
+    }
+    
+    /// Known bad, either by status or by a GoodTools style `[b]` tag in the name.
+    pub fn is_bad(&self) -> bool {
+        self.status == DumpStatus::BadDump || self.game.contains("[b") || self.name.contains("[b")
+    }
+    
+    /// Tagged as an overdump with a GoodTools style `[o]` tag.
+    pub fn is_overdump(&self) -> bool {
+        self.game.contains("[o") || self.name.contains("[o")
+    }
+}
+
+#[derive(Debug)]
+pub enum DatError {
+    Io(std::io::Error),
+    Xml(roxmltree::Error),
+    /// A ClrMamePro DAT with unbalanced parentheses or quotes.
+    Syntax(String),
+}
+
+impl Display for DatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatError::Io(err) => write!(f, "{}", err),
+            DatError::Xml(err) => write!(f, "{}", err),
+            DatError::Syntax(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for DatError {}
+
+impl From<std::io::Error> for DatError {
+    fn from(err: std::io::Error) -> Self {
+        DatError::Io(err)
+    }
+}
+
+impl From<roxmltree::Error> for DatError {
+    fn from(err: roxmltree::Error) -> Self {
+        DatError::Xml(err)
+    }
+}
+
+/// What a DAT file says about a ROM.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Identification<'a> {
+    /// The matching entry, if any.
+    pub entry: Option<&'a DatEntry>,
+    /// Whether the original file matched, rather than the big-endian image.
+    pub original: bool,
+    /// The file is bigger than the entry, and only its start matches.
+    pub overdump: bool,
+}
+
+impl<'a> Identification<'a> {
+    pub fn title(&self) -> Option<&'a str> {
+        self.entry.map(|entry| entry.game.as_str())
+    }
+    
+    pub fn is_unknown(&self) -> bool {
+        self.entry.is_none()
+    }
+    
+    pub fn is_bad(&self) -> bool {
+        self.entry.is_some_and(|entry| entry.is_bad())
+    }
+    
+    pub fn is_overdump(&self) -> bool {
+        self.overdump || self.entry.is_some_and(|entry| entry.is_overdump())
+    }
+}
+
+/// The entries of a Logiqx XML or ClrMamePro DAT file.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Dat {
+    /// Name from the header, e.g. `Nintendo - Nintendo 64 (BigEndian)`.
+    pub name: Option<String>,
+    pub entries: Vec<DatEntry>,
+}
+
+impl Dat {
+    /// Parses either format, telling them apart by the first character.
+    pub fn parse(text: &str) -> Result<Dat, DatError> {
+        match text.trim_start().starts_with('<') {
+            true => Dat::parse_xml(text),
+            false => Dat::parse_clrmamepro(text),
+        }
+    }
+    
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Dat, DatError> {
+        Dat::parse(&std::fs::read_to_string(path)?)
+    }
+    
+    pub fn parse_xml(text: &str) -> Result<Dat, DatError> {
+        let options = roxmltree::ParsingOptions { allow_dtd: true, ..roxmltree::ParsingOptions::default() };
+        let doc = roxmltree::Document::parse_with_options(text, options)?;
+        let root = doc.root_element();
+        
+        let name = root.children()
+            .find(|node| node.has_tag_name("header"))
+            .and_then(|header| header.children().find(|node| node.has_tag_name("name")))
+            .and_then(|name| name.text())
+            .map(String::from);
+        
+        let mut entries = Vec::new();
+        for game in root.children().filter(|node| node.has_tag_name("game") || node.has_tag_name("machine")) {
+            let game_name = game.attribute("name").unwrap_or("");
+            
+            for rom in game.children().filter(|node| node.has_tag_name("rom")) {
+                entries.push(entry(game_name, |key| rom.attribute(key)));
+            }
+        }
+        
+        Ok(Dat { name, entries })
+    }
+    
+    pub fn parse_clrmamepro(text: &str) -> Result<Dat, DatError> {
+        let tokens = tokenize(text)?;
+        let mut dat = Dat::default();
+        
+        let mut i = 0;
+        while i < tokens.len() {
+            let (kind, body, next) = block(&tokens, i)?;
+            i = next;
+            
+            match kind {
+                "clrmamepro" => dat.name = pairs(body).into_iter().find(|(key, _)| *key == "name").map(|(_, value)| value.to_string()),
+                "game" | "resource" | "machine" => {
+                    let fields = pairs(body);
+                    let game_name = fields.iter().find(|(key, _)| *key == "name").map(|(_, value)| *value).unwrap_or("");
+                    
+                    let mut j = 0;
+                    while j < body.len() {
+                        match (body[j], body.get(j + 1)) {
+                            (Token::Word(key), Some(Token::Open)) => {
+                                let (_, rom, next) = block(body, j)?;
+                                if key == "rom" {
+                                    let fields = pairs(rom);
+                                    dat.entries.push(entry(game_name, |key| fields.iter().find(|(name, _)| *name == key).map(|(_, value)| *value)));
+                                }
+                                j = next;
+                            },
+                            _ => j += 1,
+                        }
+                    }
+                },
+                _ => ()
+            }
+        }
+        
+        Ok(dat)
+    }
+    
+    /// Looks up a ROM by the hashes of its big-endian image, then of the original file. Failing that, checks whether the
+    /// ROM starts with a known ROM, which makes it an overdump.
+    pub fn identify(&self, rom: &Rom) -> Identification<'_> {
+        let normalized = rom.hashes();
+        if let Some(entry) = self.find(&normalized) {
+            return Identification { entry: Some(entry), original: false, overdump: false };
+        }
+        
+        let original = rom.original_hashes();
+        if let Some(entry) = self.find(&original) {
+            return Identification { entry: Some(entry), original: true, overdump: false };
+        }
+        
+        // Largest first, so the longest known ROM wins. Only sizes with a matching CRC32 get the slower hashes computed.
+        let sizes: BTreeSet<usize> = self.entries.iter().filter_map(|entry| entry.size).filter(|size| *size < rom.data().len()).collect();
+        for size in sizes.into_iter().rev() {
+            let data = &rom.data()[..size];
+            let crc32 = crc32fast::hash(data);
+            let mut candidates = self.entries.iter()
+                .filter(|entry| entry.size == Some(size) && entry.crc32.is_none_or(|crc| crc == crc32))
+                .peekable();
+            
+            if candidates.peek().is_some() {
+                let hashes = Hashes::of(data);
+                if let Some(entry) = candidates.find(|entry| entry.matches(&hashes)) {
+                    return Identification { entry: Some(entry), original: false, overdump: true };
+                }
+            }
+        }
+        
+        Identification { entry: None, original: false, overdump: false }
+    }
+    
+    pub fn find(&self, hashes: &Hashes) -> Option<&DatEntry> {
+        self.entries.iter().find(|entry| entry.matches(hashes))
+    }
+}
+
+fn entry<'a, F: Fn(&str) -> Option<&'a str>>(game: &str, field: F) -> DatEntry {
+    DatEntry {
+        game: game.to_string(),
+        name: field("name").unwrap_or("").to_string(),
+        size: field("size").and_then(|size| size.parse().ok()),
+        crc32: field("crc").and_then(|crc| u32::from_str_radix(crc, 16).ok()),
+        md5: field("md5").and_then(from_hex),
+        sha1: field("sha1").and_then(from_hex),
+        status: DumpStatus::parse(field("status").or_else(|| field("flags")).unwrap_or("")),
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum Token<'a> {
+    Open,
+    Close,
+    Word(&'a str),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token<'_>>, DatError> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            '(' => { tokens.push(Token::Open); 1 },
+            ')' => { tokens.push(Token::Close); 1 },
+            '"' => {
+                let end = rest[1..].find('"').ok_or_else(|| DatError::Syntax(String::from("unterminated string")))?;
+                tokens.push(Token::Word(&rest[1..(end + 1)]));
+                end + 2
+            },
+            _ => {
+                let end = rest.find(|c: char| c.is_whitespace() || c == '(' || c == ')').unwrap_or(rest.len());
+                tokens.push(Token::Word(&rest[..end]));
+                end
+            },
+        };
+        rest = rest[len..].trim_start();
+    }
+    
+    Ok(tokens)
+}
+
+/// Splits `name ( ... )` starting at `start`, returning the name, the tokens inside the parentheses and the index after
+/// the closing one.
+fn block<'t, 'a>(tokens: &'t [Token<'a>], start: usize) -> Result<(&'a str, &'t [Token<'a>], usize), DatError> {
+    let name = match (tokens.get(start), tokens.get(start + 1)) {
+        (Some(Token::Word(name)), Some(Token::Open)) => *name,
+        _ => return Err(DatError::Syntax(format!("expected `name (` at token {}", start))),
+    };
+    
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start + 1) {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close if depth == 1 => return Ok((name, &tokens[(start + 2)..i], i + 1)),
+            Token::Close => depth -= 1,
+            Token::Word(_) => (),
+        }
+    }
+    
+    Err(DatError::Syntax(format!("unclosed `{} (`", name)))
+}
+
+/// The `key value` pairs directly inside a block, skipping nested blocks.
+fn pairs<'a>(tokens: &[Token<'a>]) -> Vec<(&'a str, &'a str)> {
+    let mut pairs = Vec::new();
+    let mut depth = 0;
+    
+    let mut i = 0;
+    while i < tokens.len() {
+        match (tokens[i], tokens.get(i + 1)) {
+            (Token::Open, _) => depth += 1,
+            (Token::Close, _) => depth -= 1,
+            (Token::Word(key), Some(Token::Word(value))) if depth == 0 => {
+                pairs.push((key, *value));
+                i += 1;
+            },
+            _ => ()
+        }
+        i += 1;
+    }
+    
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dat, DatError, DumpStatus};
+    use crate::hash::{to_hex, Hashes};
+    use crate::rom::{test_rom, ByteOrder, Rom};
+    
+    fn image(seed: u8) -> Vec<u8> {
+        test_rom(0x2000).with_fill(|i| (i as u8).wrapping_mul(seed)).into_bytes()
+    }
+    
+    fn xml() -> String {
+        let good = Hashes::of(&image(3));
+        let bad = Hashes::of(&image(5));
+        
+        format!(r#"<?xml version="1.0"?>
+<!DOCTYPE datafile PUBLIC "-//Logiqx//DTD ROM Management Datafile//EN" "http://www.logiqx.com/dtds/datafile.dtd">
+<datafile>
+    <header>
+        <name>Nintendo - Nintendo 64 (BigEndian)</name>
+    </header>
+    <game name="Good Game (USA)">
+        <description>Good Game (USA)</description>
+        <rom name="Good Game (USA).z64" size="{}" crc="{:08X}" md5="{}" sha1="{}" status="verified"/>
+    </game>
+    <game name="Bad Game (Europe)">
+        <rom name="Bad Game (Europe).z64" size="{}" crc="{:08X}" status="baddump"/>
+    </game>
+    <game name="Missing Game (Japan)">
+        <rom name="Missing Game (Japan).z64" size="8388608" status="nodump"/>
+    </game>
+</datafile>
+"#, good.size, good.crc32, good.md5_hex(), good.sha1_hex(), bad.size, bad.crc32)
+    }
+    
+    #[test]
+    fn logiqx() {
+        let dat = Dat::parse(&xml()).unwrap();
+        
+        assert_eq!(dat.name.as_deref(), Some("Nintendo - Nintendo 64 (BigEndian)"));
+        assert_eq!(dat.entries.len(), 3);
+        assert_eq!(dat.entries[0].game, "Good Game (USA)");
+        assert_eq!(dat.entries[0].status, DumpStatus::Verified);
+        assert_eq!(dat.entries[0].sha1, Some(Hashes::of(&image(3)).sha1));
+        assert_eq!(dat.entries[1].md5, None);
+        assert_eq!(dat.entries[2].status, DumpStatus::NoDump);
+        assert_eq!(dat.entries[2].size, Some(0x800000));
+        
+        assert!(matches!(Dat::parse("<datafile><game>"), Err(DatError::Xml(_))));
+    }
+    
+    #[test]
+    fn clrmamepro() {
+        let good = Hashes::of(&image(3));
+        let text = format!(r#"clrmamepro (
+	name "Nintendo - Nintendo 64 (BigEndian)"
+	version 20201010-123456
+)
+
+game (
+	name "Good Game (USA)"
+	description "Good Game (USA)"
+	rom ( name "Good Game (USA).z64" size {} crc {:08X} md5 {} sha1 {} flags verified )
+)
+
+game (
+	name "Some Hack [o1]"
+	rom ( name "Some Hack [o1].z64" size 16 crc 00000000 )
+)
+"#, good.size, good.crc32, to_hex(&good.md5).to_uppercase(), good.sha1_hex());
+        let dat = Dat::parse(&text).unwrap();
+        
+        assert_eq!(dat.name.as_deref(), Some("Nintendo - Nintendo 64 (BigEndian)"));
+        assert_eq!(dat.entries.len(), 2);
+        assert_eq!(dat.entries[0], Dat::parse(&xml()).unwrap().entries[0]);
+        assert_eq!(dat.entries[1].game, "Some Hack [o1]");
+        assert!(dat.entries[1].is_overdump());
+        assert!(!dat.entries[0].is_overdump());
+        
+        assert!(matches!(Dat::parse("game ( name \"x\" "), Err(DatError::Syntax(_))));
+        assert!(matches!(Dat::parse("game ( name \"x )"), Err(DatError::Syntax(_))));
+    }
+    
+    #[test]
+    fn identification() {
+        let dat = Dat::parse(&xml()).unwrap();
+        
+        let good = Rom::new(image(3)).unwrap();
+        let id = dat.identify(&good);
+        assert_eq!(id.title(), Some("Good Game (USA)"));
+        assert!(!id.is_bad() && !id.is_overdump() && !id.original);
+        
+        let mut swapped = image(3);
+        ByteOrder::LittleEndian.swap(&mut swapped);
+        assert_eq!(dat.identify(&Rom::new(swapped).unwrap()).title(), Some("Good Game (USA)"));
+        
+        let bad = dat.identify(&Rom::new(image(5)).unwrap());
+        assert_eq!(bad.title(), Some("Bad Game (Europe)"));
+        assert!(bad.is_bad());
+        
+        let mut overdump = image(3);
+        overdump.extend_from_slice(&[0xFF; 0x1000]);
+        let id = dat.identify(&Rom::new(overdump.clone()).unwrap());
+        assert_eq!(id.title(), Some("Good Game (USA)"));
+        assert!(id.is_overdump());
+        
+        // A known ROM that is itself a prefix of the overdump doesn't shadow the longer match
+        let mut prefixed = Dat::parse(&xml()).unwrap();
+        let half = Hashes::of(&image(3)[..0x1000]);
+        prefixed.entries[2].size = Some(half.size);
+        prefixed.entries[2].crc32 = Some(half.crc32);
+        prefixed.entries[2].sha1 = Some(half.sha1);
+        assert_eq!(prefixed.identify(&Rom::new(overdump).unwrap()).title(), Some("Good Game (USA)"));
+        assert_eq!(prefixed.identify(&Rom::new(image(3)[..0x1800].to_vec()).unwrap()).title(), Some("Missing Game (Japan)"));
+        
+        let unknown = dat.identify(&Rom::new(image(7)).unwrap());
+        assert!(unknown.is_unknown());
+        assert_eq!(unknown.title(), None);
+        
+        let mut v64 = image(3);
+        ByteOrder::ByteSwapped.swap(&mut v64);
+        let mut original_only = Dat::parse(&xml()).unwrap();
+        original_only.entries[0].sha1 = Some(Hashes::of(&v64).sha1);
+        assert!(original_only.identify(&Rom::new(v64).unwrap()).original);
+    }
+}
@@ -1,45 +1,251 @@
-use crate::rom::Rom;
+use crate::rsp::{disassemble_rsp, element_suffix, RSP_CP0_REG_NAMES, VU_ACC_NAMES, VU_CTRL_REG_NAMES};
 use std::fmt::{Display, Formatter};
 use std::ops::Range;
-use colored::*;
 
-const CPU_REG_NAMES: [&'static str; 32] = [
+pub(crate) const CPU_REG_NAMES: [&str; 32] = [
     "zr", "at", "v0", "v1", "a0", "a1", "a2", "a3", "t0", "t1", "t2", "t3", "t4", "t5", "t6", "t7",
     "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "t8", "t9", "k0", "k1", "gp", "sp", "fp", "ra"
 ];
 
-const CP0_REG_NAMES: [&'static str; 32] = [
+pub(crate) const O32_REG_NAMES: [&str; 32] = [
+    "zero", "at", "v0", "v1", "a0", "a1", "a2", "a3", "t0", "t1", "t2", "t3", "t4", "t5", "t6", "t7",
+    "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "t8", "t9", "k0", "k1", "gp", "sp", "s8", "ra"
+];
+
+const N32_REG_NAMES: [&str; 32] = [
+    "zero", "at", "v0", "v1", "a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7", "t0", "t1", "t2", "t3",
+    "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "t8", "t9", "k0", "k1", "gp", "sp", "s8", "ra"
+];
+
+pub(crate) const CP0_REG_NAMES: [&str; 32] = [
     "Index", "Random", "EntryLo0", "EntryLo1", "Context", "PageMask", "Wired", "Unused7", "BadVAddr", "Count", "EntryHi", "Compare", "SR", "Cause", "EPC", "PRId",
     "Config", "LLAddr", "WatchLo", "WatchHi", "XContext", "Unused21", "Unused22", "Unused23", "Unused24", "Unused25", "PErr", "Unused27", "TagLo", "TagHi", "ErrorEPC", "Unused31"
 ];
 
+// Indexed by bits 4..2 (operation) and 1..0 (cache) of the CACHE `op` field. Empty entries are reserved.
+const CACHE_OP_NAMES: [[&str; 2]; 8] = [
+    ["Index_Invalidate_I", "Index_Writeback_Invalidate_D"],
+    ["Index_Load_Tag_I", "Index_Load_Tag_D"],
+    ["Index_Store_Tag_I", "Index_Store_Tag_D"],
+    ["", "Create_Dirty_Exclusive_D"],
+    ["Hit_Invalidate_I", "Hit_Invalidate_D"],
+    ["Fill_I", "Hit_Writeback_Invalidate_D"],
+    ["Hit_Writeback_I", "Hit_Writeback_D"],
+    ["", ""],
+];
+
+pub fn cache_op_name(op: u8) -> Option<&'static str> {
+    match op & 0x03 {
+        0 | 1 => Some(CACHE_OP_NAMES[(op >> 2) as usize & 0x07][(op & 0x03) as usize]).filter(|name| !name.is_empty()),
+        _ => None
+    }
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Operand {
     Reg(u8),
     Cp0Reg(u8),
+    Cp1Reg(u8),
+    Cp1CtrlReg(u8),
+    RspCp0Reg(u8),
+    VReg(u8),
+    VRegElement(u8, u8),
+    VRegLane(u8, u8),
+    VCtrlReg(u8),
+    VAcc(u8),
+    JumpTarget(u32),
+    Branch(i16),
+    Addr(u32),
+    Mem(u8, i16),
+    Imm16(i16),
+    CacheOp(u8),
+    Code(u32),
+    CopReg(u8),
     Lit8(u8),
     Lit16(u16),
     Lit32(u32),
 }
 
-impl Display for Operand {
+/// Naming convention used for CPU registers.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum RegScheme {
+    /// Our compact ABI names (`zr`, `fp`).
+    Compact,
+    /// GNU o32 ABI names (`zero`, `s8`).
+    O32,
+    /// GNU n32/n64 ABI names (`a4`-`a7` in place of `t0`-`t3`).
+    N32,
+    /// Plain register numbers.
+    Numeric,
+}
+
+/// Register naming options applied to every register operand when displaying instructions.
+/// 
+/// With `dollar` set, names are prefixed with `$` (`$sp`, `$f4`, `$v3`). Assemblers only take coprocessor control
+/// registers as `$` and a number, so CP0, FCR and vector control registers are printed that way (`$12`, `$31`).
+/// Without it, numeric names get a letter prefix instead so they can't be confused with immediates (`r29`, `c0r12`).
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct RegNames {
+    pub scheme: RegScheme,
+    pub dollar: bool,
+}
+
+impl Default for RegNames {
+    fn default() -> Self {
+        RegNames { scheme: RegScheme::Compact, dollar: false }
+    }
+}
+
+impl RegNames {
+    pub fn new(scheme: RegScheme, dollar: bool) -> RegNames {
+        RegNames { scheme, dollar }
+    }
+    
+    fn prefix(&self) -> &'static str {
+        if self.dollar { "$" } else { "" }
+    }
+    
+    pub fn cpu(&self, reg: u8) -> String {
+        let reg = (reg & 0x1F) as usize;
+        
+        match self.scheme {
+            RegScheme::Compact => format!("{}{}", self.prefix(), CPU_REG_NAMES[reg]),
+            RegScheme::O32 => format!("{}{}", self.prefix(), O32_REG_NAMES[reg]),
+            RegScheme::N32 => format!("{}{}", self.prefix(), N32_REG_NAMES[reg]),
+            RegScheme::Numeric if self.dollar => format!("${}", reg),
+            RegScheme::Numeric => format!("r{}", reg),
+        }
+    }
+    
+    pub fn cp0(&self, reg: u8) -> String {
+        let reg = (reg & 0x1F) as usize;
+        
+        match self.scheme {
+            _ if self.dollar => format!("${}", reg),
+            RegScheme::Numeric => format!("c0r{}", reg),
+            _ => String::from(CP0_REG_NAMES[reg]),
+        }
+    }
+    
+    pub fn rsp_cp0(&self, reg: u8) -> String {
+        let reg = (reg & 0x0F) as usize;
+        
+        match self.scheme {
+            _ if self.dollar => format!("${}", reg),
+            RegScheme::Numeric => format!("c0r{}", reg),
+            _ => String::from(RSP_CP0_REG_NAMES[reg]),
+        }
+    }
+    
+    pub fn fpr(&self, reg: u8) -> String {
+        format!("{}f{}", self.prefix(), reg)
+    }
+    
+    pub fn fcr(&self, reg: u8) -> String {
+        match self.scheme {
+            _ if self.dollar => format!("${}", reg),
+            RegScheme::Numeric => format!("fcr{}", reg),
+            _ => format!("FCR{}", reg),
+        }
+    }
+    
+    pub fn vreg(&self, reg: u8) -> String {
+        format!("{}v{}", self.prefix(), reg)
+    }
+    
+    /// Vector control registers are numbered as in CFC2/CTC2.
+    pub fn vctrl(&self, reg: u8) -> String {
+        match self.dollar {
+            true => format!("${}", reg),
+            false => String::from(VU_CTRL_REG_NAMES[reg as usize]),
+        }
+    }
+    
+    /// Accumulator slices are numbered as the VSAR element field selects them, from 8.
+    pub fn vacc(&self, acc: u8) -> String {
+        match self.dollar {
+            true => format!("${}", acc + 8),
+            false => String::from(VU_ACC_NAMES[acc as usize]),
+        }
+    }
+    
+    pub fn cop(&self, reg: u8) -> String {
+        match self.scheme {
+            RegScheme::Numeric if !self.dollar => format!("cr{}", reg),
+            _ => format!("${}", reg),
+        }
+    }
+}
+
+impl Operand {
+    /// Displays this operand using the given register names.
+    pub fn display(&self, names: RegNames) -> OperandDisplay<'_> {
+        OperandDisplay { operand: self, names }
+    }
+}
+
+pub struct OperandDisplay<'a> {
+    operand: &'a Operand,
+    names: RegNames,
+}
+
+impl<'a> Display for OperandDisplay<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         use Operand::*;
         
-        match self {
-            Reg(reg) => write!(f, "{}", CPU_REG_NAMES[*reg as usize]),
-            Cp0Reg(reg) => write!(f, "{}", CP0_REG_NAMES[*reg as usize]),
+        let names = &self.names;
+        match self.operand {
+            Reg(reg) => write!(f, "{}", names.cpu(*reg)),
+            Cp0Reg(reg) => write!(f, "{}", names.cp0(*reg)),
+            Cp1Reg(reg) => write!(f, "{}", names.fpr(*reg)),
+            Cp1CtrlReg(reg) => write!(f, "{}", names.fcr(*reg)),
+            RspCp0Reg(reg) => write!(f, "{}", names.rsp_cp0(*reg)),
+            VReg(reg) => write!(f, "{}", names.vreg(*reg)),
+            VRegElement(reg, e) => write!(f, "{}{}", names.vreg(*reg), element_suffix(*e)),
+            VRegLane(reg, lane) => write!(f, "{}[{}]", names.vreg(*reg), lane),
+            VCtrlReg(reg) => write!(f, "{}", names.vctrl(*reg)),
+            VAcc(acc) => write!(f, "{}", names.vacc(*acc)),
+            JumpTarget(target) => write!(f, "{:#09X}", target << 2),
+            Branch(offset) if *offset < 0 => write!(f, "-{:#06X}", -(*offset as i32) * 4),
+            Branch(offset) => write!(f, "+{:#06X}", (*offset as i32) * 4),
+            Addr(addr) => write!(f, "{:#010X}", addr),
+            Mem(base, offset) => write!(f, "{}({})", Imm16(*offset), names.cpu(*base)),
+            Imm16(val) if *val < 0 => write!(f, "-{:#X}", -(*val as i32)),
+            Imm16(val) => write!(f, "{:#X}", val),
+            CacheOp(op) => match cache_op_name(*op) {
+                Some(name) => write!(f, "{}", name),
+                None => write!(f, "{:#04X}", op),
+            },
+            Code(val) => write!(f, "{:#X}", val),
+            CopReg(reg) => write!(f, "{}", names.cop(*reg)),
             Lit8(val) => write!(f, "{:#04X}", val),
             Lit16(val) => write!(f, "{:#06X}", val),
             Lit32(val) => write!(f, "{:#010X}", val),
-            _ => write!(f, "{:?}", self)
         }
-        
     }
 }
 
-#[allow(dead_code)]
-#[derive(PartialEq, strum_macros::ToString, Copy, Clone)]
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display(RegNames::default()))
+    }
+}
+
+/// Joins the present operands with `, `.
+pub fn format_args(args: &[Option<Operand>], names: RegNames) -> String {
+    let mut out = String::new();
+    for arg in args.iter().flatten() {
+        if !out.is_empty() {
+            out.push_str(", ")
+        }
+        out.push_str(&arg.display(names).to_string());
+    }
+    
+    out
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, strum_macros::Display, strum_macros::EnumIter, strum_macros::EnumString, Copy, Clone)]
 pub enum Operation {
     ADD,
     ADDI,
@@ -200,75 +406,324 @@ pub enum Operation {
     XOR,
     XORI,
     
+    BC1F,
+    BC1FL,
+    BC1T,
+    BC1TL,
+    
+    CFC1,
+    CTC1,
+    DMFC1,
+    DMTC1,
+    MFC1,
+    MTC1,
+    
+    LDC1,
+    LWC1,
+    SDC1,
+    SWC1,
+    
+    #[strum(serialize = "ADD.S")] ADD_S,
+    #[strum(serialize = "ADD.D")] ADD_D,
+    
+    #[strum(serialize = "SUB.S")] SUB_S,
+    #[strum(serialize = "SUB.D")] SUB_D,
+    
+    #[strum(serialize = "MUL.S")] MUL_S,
+    #[strum(serialize = "MUL.D")] MUL_D,
+    
+    #[strum(serialize = "DIV.S")] DIV_S,
+    #[strum(serialize = "DIV.D")] DIV_D,
+    
+    #[strum(serialize = "SQRT.S")] SQRT_S,
+    #[strum(serialize = "SQRT.D")] SQRT_D,
+    
+    #[strum(serialize = "ABS.S")] ABS_S,
+    #[strum(serialize = "ABS.D")] ABS_D,
+    
+    #[strum(serialize = "MOV.S")] MOV_S,
+    #[strum(serialize = "MOV.D")] MOV_D,
+    
+    #[strum(serialize = "NEG.S")] NEG_S,
+    #[strum(serialize = "NEG.D")] NEG_D,
+    
+    #[strum(serialize = "ROUND.L.S")] ROUND_L_S,
+    #[strum(serialize = "ROUND.L.D")] ROUND_L_D,
+    #[strum(serialize = "ROUND.W.S")] ROUND_W_S,
+    #[strum(serialize = "ROUND.W.D")] ROUND_W_D,
+    
+    #[strum(serialize = "TRUNC.L.S")] TRUNC_L_S,
+    #[strum(serialize = "TRUNC.L.D")] TRUNC_L_D,
+    #[strum(serialize = "TRUNC.W.S")] TRUNC_W_S,
+    #[strum(serialize = "TRUNC.W.D")] TRUNC_W_D,
+    
+    #[strum(serialize = "CEIL.L.S")] CEIL_L_S,
+    #[strum(serialize = "CEIL.L.D")] CEIL_L_D,
+    #[strum(serialize = "CEIL.W.S")] CEIL_W_S,
+    #[strum(serialize = "CEIL.W.D")] CEIL_W_D,
+    
+    #[strum(serialize = "FLOOR.L.S")] FLOOR_L_S,
+    #[strum(serialize = "FLOOR.L.D")] FLOOR_L_D,
+    #[strum(serialize = "FLOOR.W.S")] FLOOR_W_S,
+    #[strum(serialize = "FLOOR.W.D")] FLOOR_W_D,
+    
+    #[strum(serialize = "CVT.S.D")] CVT_S_D,
+    #[strum(serialize = "CVT.S.W")] CVT_S_W,
+    #[strum(serialize = "CVT.S.L")] CVT_S_L,
+    #[strum(serialize = "CVT.D.S")] CVT_D_S,
+    #[strum(serialize = "CVT.D.W")] CVT_D_W,
+    #[strum(serialize = "CVT.D.L")] CVT_D_L,
+    #[strum(serialize = "CVT.W.S")] CVT_W_S,
+    #[strum(serialize = "CVT.W.D")] CVT_W_D,
+    #[strum(serialize = "CVT.L.S")] CVT_L_S,
+    #[strum(serialize = "CVT.L.D")] CVT_L_D,
+    
+    #[strum(serialize = "C.F.S")] C_F_S,
+    #[strum(serialize = "C.UN.S")] C_UN_S,
+    #[strum(serialize = "C.EQ.S")] C_EQ_S,
+    #[strum(serialize = "C.UEQ.S")] C_UEQ_S,
+    #[strum(serialize = "C.OLT.S")] C_OLT_S,
+    #[strum(serialize = "C.ULT.S")] C_ULT_S,
+    #[strum(serialize = "C.OLE.S")] C_OLE_S,
+    #[strum(serialize = "C.ULE.S")] C_ULE_S,
+    #[strum(serialize = "C.SF.S")] C_SF_S,
+    #[strum(serialize = "C.NGLE.S")] C_NGLE_S,
+    #[strum(serialize = "C.SEQ.S")] C_SEQ_S,
+    #[strum(serialize = "C.NGL.S")] C_NGL_S,
+    #[strum(serialize = "C.LT.S")] C_LT_S,
+    #[strum(serialize = "C.NGE.S")] C_NGE_S,
+    #[strum(serialize = "C.LE.S")] C_LE_S,
+    #[strum(serialize = "C.NGT.S")] C_NGT_S,
+    
+    #[strum(serialize = "C.F.D")] C_F_D,
+    #[strum(serialize = "C.UN.D")] C_UN_D,
+    #[strum(serialize = "C.EQ.D")] C_EQ_D,
+    #[strum(serialize = "C.UEQ.D")] C_UEQ_D,
+    #[strum(serialize = "C.OLT.D")] C_OLT_D,
+    #[strum(serialize = "C.ULT.D")] C_ULT_D,
+    #[strum(serialize = "C.OLE.D")] C_OLE_D,
+    #[strum(serialize = "C.ULE.D")] C_ULE_D,
+    #[strum(serialize = "C.SF.D")] C_SF_D,
+    #[strum(serialize = "C.NGLE.D")] C_NGLE_D,
+    #[strum(serialize = "C.SEQ.D")] C_SEQ_D,
+    #[strum(serialize = "C.NGL.D")] C_NGL_D,
+    #[strum(serialize = "C.LT.D")] C_LT_D,
+    #[strum(serialize = "C.NGE.D")] C_NGE_D,
+    #[strum(serialize = "C.LE.D")] C_LE_D,
+    #[strum(serialize = "C.NGT.D")] C_NGT_D,
+    
+    CFC2,
+    CTC2,
+    MFC2,
+    MTC2,
+    
+    VMULF,
+    VMULU,
+    VRNDP,
+    VMULQ,
+    VMUDL,
+    VMUDM,
+    VMUDN,
+    VMUDH,
+    VMACF,
+    VMACU,
+    VRNDN,
+    VMACQ,
+    VMADL,
+    VMADM,
+    VMADN,
+    VMADH,
+    
+    VADD,
+    VSUB,
+    VABS,
+    VADDC,
+    VSUBC,
+    VSAR,
+    
+    VLT,
+    VEQ,
+    VNE,
+    VGE,
+    VCL,
+    VCH,
+    VCR,
+    VMRG,
+    VAND,
+    VNAND,
+    VOR,
+    VNOR,
+    VXOR,
+    VNXOR,
+    
+    VRCP,
+    VRCPL,
+    VRCPH,
+    VMOV,
+    VRSQ,
+    VRSQL,
+    VRSQH,
+    VNOP,
+    
+    LBV,
+    LSV,
+    LLV,
+    LDV,
+    LQV,
+    LRV,
+    LPV,
+    LUV,
+    LHV,
+    LFV,
+    LWV,
+    LTV,
+    
+    SBV,
+    SSV,
+    SLV,
+    SDV,
+    SQV,
+    SRV,
+    SPV,
+    SUV,
+    SHV,
+    SFV,
+    SWV,
+    STV,
+    
     NOP,
     Unknown,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub struct Instruction {
     pub code: u32,
     pub op: Operation,
     pub args: [Option<Operand>; 4],
 }
 
-impl Clone for Instruction {
-    fn clone(&self) -> Self {
-        let mut args = [None, None, None, None];
-        for i in 0..args.len() {
-            if self.args[i].is_some() {
-                args[i] = Some(self.args[i].unwrap());
-            }
-        }
-        
-        Instruction {
-            code: self.code,
-            op: self.op,
-            args: args,
-        }
-    }
-}
-
 impl Instruction {
     pub fn new0(code: u32, op: Operation) -> Instruction {
-        Instruction { code: code, op: op, args: [None,        None,        None,        None       ] }
+        Instruction { code, op, args: [None,        None,        None,        None       ] }
     }
     
     pub fn new1(code: u32, op: Operation, oper0: Operand) -> Instruction {
-        Instruction { code: code, op: op, args: [Some(oper0), None,        None,        None       ] }
+        Instruction { code, op, args: [Some(oper0), None,        None,        None       ] }
     }
     
     pub fn new2(code: u32, op: Operation, oper0: Operand, oper1: Operand) -> Instruction {
-        Instruction { code: code, op: op, args: [Some(oper0), Some(oper1), None,        None       ] }
+        Instruction { code, op, args: [Some(oper0), Some(oper1), None,        None       ] }
     }
     
     pub fn new3(code: u32, op: Operation, oper0: Operand, oper1: Operand, oper2: Operand) -> Instruction {
-        Instruction { code: code, op: op, args: [Some(oper0), Some(oper1), Some(oper2), None       ] }
+        Instruction { code, op, args: [Some(oper0), Some(oper1), Some(oper2), None       ] }
     }
     
     pub fn new4(code: u32, op: Operation, oper0: Operand, oper1: Operand, oper2: Operand, oper3: Operand) -> Instruction {
-        Instruction { code: code, op: op, args: [Some(oper0), Some(oper1), Some(oper2), Some(oper3)] }
+        Instruction { code, op, args: [Some(oper0), Some(oper1), Some(oper2), Some(oper3)] }
     }
-}
-
-impl Display for Instruction {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut args = String::new();
-        for arg in self.args.iter() {
-            if arg.is_some() {
-                if !args.is_empty() {
-                    args.push_str(", ")
+    
+    /// The code field of SYSCALL/BREAK (20 bits), the register-register traps (10 bits) or the SYNC type.
+    pub fn code_field(&self) -> Option<u32> {
+        use Operation::*;
+        
+        match self.op {
+            SYSCALL | BREAK => Some((self.code >> 6) & 0xFFFFF),
+            TGE | TGEU | TLT | TLTU | TEQ | TNE => Some((self.code >> 6) & 0x3FF),
+            SYNC => Some((self.code >> 6) & 0x1F),
+            _ => None
+        }
+    }
+    
+    /// Computes the absolute destination of a J/JAL, given the address of the instruction itself.
+    /// 
+    /// The VR4300 takes the upper 4 bits from the address of the delay slot, while the RSP only has a 12-bit PC
+    /// and keeps everything above the IMEM offset.
+    pub fn jump_target(&self, pc: u32, target: Target) -> Option<u32> {
+        self.args.iter().find_map(|arg| match arg {
+            Some(Operand::JumpTarget(field)) => Some(match target {
+                Target::VR4300 => (pc.wrapping_add(4) & 0xF0000000) | (field << 2),
+                Target::RSP => (pc & 0xFFFFF000) | ((field << 2) & 0xFFC),
+            }),
+            _ => None
+        })
+    }
+    
+    /// Computes the absolute destination of a PC-relative branch, given the address of the instruction itself.
+    /// 
+    /// The offset is relative to the delay slot. On the RSP the result wraps within the 4 KiB of IMEM.
+    pub fn branch_target(&self, pc: u32, target: Target) -> Option<u32> {
+        self.args.iter().find_map(|arg| match arg {
+            Some(Operand::Branch(offset)) => {
+                let dest = pc.wrapping_add(4).wrapping_add(((*offset as i32) << 2) as u32);
+                
+                Some(match target {
+                    Target::VR4300 => dest,
+                    Target::RSP => (pc & 0xFFFFF000) | (dest & 0xFFC),
+                })
+            },
+            _ => None
+        })
+    }
+    
+    /// Destination of any jump or branch instruction, excluding register jumps (JR/JALR).
+    pub fn destination(&self, pc: u32, target: Target) -> Option<u32> {
+        self.jump_target(pc, target).or_else(|| self.branch_target(pc, target))
+    }
+    
+    /// Replaces any address relative operands with their absolute [`Operand::Addr`] equivalents.
+    pub fn resolve(&mut self, pc: u32, target: Target) {
+        if let Some(addr) = self.destination(pc, target) {
+            for arg in self.args.iter_mut() {
+                if let Some(Operand::JumpTarget(_)) | Some(Operand::Branch(_)) = arg {
+                    *arg = Some(Operand::Addr(addr));
                 }
-                args.push_str(&arg.as_ref().unwrap().to_string());
             }
         }
+    }
+}
+
+impl Instruction {
+    /// Displays this instruction using the given register names.
+    pub fn display(&self, names: RegNames) -> InstructionDisplay<'_> {
+        InstructionDisplay { instr: self, names }
+    }
+}
+
+pub struct InstructionDisplay<'a> {
+    instr: &'a Instruction,
+    names: RegNames,
+}
+
+impl<'a> Display for InstructionDisplay<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let args = format_args(&self.instr.args, self.names);
         
-        //write!(f, "[{:032b}] [{}] [{}]", self.code, self.op.to_string(), args)
-        write!(f, "[{:#010X}][{} {}]", self.code, self.op.to_string(), args)
+        //write!(f, "[{:032b}] [{}] [{}]", self.code, self.op, args)
+        write!(f, "[{:#010X}][{} {}]", self.instr.code, self.instr.op, args)
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display(RegNames::default()))
     }
 }
 
+/// The processor a block of code is decoded for.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Target {
+    VR4300,
+    RSP,
+}
+
 #[derive(Clone)]
 pub struct Disassembly {
+    pub target: Target,
+    /// Virtual address of the first instruction.
+    pub vaddr: Option<u32>,
+    /// Offset of the first instruction within the ROM (or other file) it was read from.
+    pub rom_offset: Option<u32>,
     pub raw: Vec<u32>,
     pub instructions: Vec<Instruction>,
 }
@@ -280,7 +735,11 @@ impl PartialEq for Disassembly {
 }
 
 impl Disassembly {
-    pub fn from_u8(raw_u8: &Vec<u8>) -> Disassembly {
+    pub fn from_u8(raw_u8: &[u8]) -> Disassembly {
+        Disassembly::from_u8_target(raw_u8, Target::VR4300)
+    }
+    
+    pub fn from_u8_target(raw_u8: &[u8], target: Target) -> Disassembly {
         let len = raw_u8.len() - (raw_u8.len() % 4);
         let mut raw = Vec::new();
         let mut instructions = Vec::new();
@@ -289,26 +748,155 @@ impl Disassembly {
             let slice = &raw_u8[(i * 4)..((i * 4) + 4)];
             
             raw.push(to_u32(slice));
-            instructions.push(disassemble_u8(slice));
+            instructions.push(disassemble_target(to_u32(slice), target));
         }
         
         Disassembly {
-            raw: raw,
-            instructions: instructions,
+            target,
+            vaddr: None,
+            rom_offset: None,
+            raw,
+            instructions,
         }
     }
     
-    pub fn from_u32(raw_u32: &Vec<u32>) -> Disassembly {
-        let len = raw_u32.len();
+    pub fn from_u32(raw_u32: &[u32]) -> Disassembly {
+        Disassembly::from_u32_target(raw_u32, Target::VR4300)
+    }
+    
+    pub fn from_u32_target(raw_u32: &[u32], target: Target) -> Disassembly {
         let mut instructions = Vec::new();
         
-        for i in 0..len {
-            instructions.push(disassemble(raw_u32[i]));
+        for code in raw_u32 {
+            instructions.push(disassemble_target(*code, target));
         }
         
         Disassembly {
-            raw: raw_u32.clone(),
-            instructions: instructions,
+            target,
+            vaddr: None,
+            rom_offset: None,
+            raw: raw_u32.to_vec(),
+            instructions,
+        }
+    }
+    
+    /// Disassembles `len` bytes of `rom` starting at `offset`, mapped at `vaddr`. The range is clamped to the end of
+    /// `rom`, and a trailing partial word is left out.
+    pub fn from_rom(rom: &[u8], offset: usize, len: usize, vaddr: u32, target: Target) -> Disassembly {
+        let start = offset.min(rom.len());
+        let end = offset.saturating_add(len).min(rom.len());
+        
+        Disassembly::from_u8_target(&rom[start..end], target).at_vaddr(vaddr).with_rom_offset(offset as u32)
+    }
+    
+    /// Address of the instruction at `index`. Without a known vaddr, this is the offset from the start of the block.
+    pub fn addr_of(&self, index: usize) -> u32 {
+        self.vaddr.unwrap_or(0).wrapping_add(index as u32 * 4)
+    }
+    
+    /// Resolved destination of the jump or branch at `index`, if it is one.
+    pub fn destination(&self, index: usize) -> Option<u32> {
+        let addr = self.addr_of(index);
+        let instr = self.instructions.get(index)?;
+        
+        match instr.args.iter().find_map(|arg| match arg { Some(Operand::Addr(addr)) => Some(*addr), _ => None }) {
+            Some(resolved) => Some(resolved),
+            None => instr.destination(addr, self.target),
+        }
+    }
+    
+    /// Maps the first instruction to `vaddr`, resolving jump and branch targets to absolute addresses.
+    pub fn at_vaddr(mut self, vaddr: u32) -> Disassembly {
+        for (i, instr) in self.instructions.iter_mut().enumerate() {
+            *instr = disassemble_target(self.raw[i], self.target);
+            instr.resolve(vaddr.wrapping_add(i as u32 * 4), self.target);
+        }
+        self.vaddr = Some(vaddr);
+        
+        self
+    }
+    
+    /// Records where in the ROM the first instruction was read from.
+    pub fn with_rom_offset(mut self, rom_offset: u32) -> Disassembly {
+        self.rom_offset = Some(rom_offset);
+        
+        self
+    }
+    
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+    
+    /// Index of the instruction at `addr`, if it is word aligned and within this block.
+    pub fn index_of(&self, addr: u32) -> Option<usize> {
+        let offset = addr.wrapping_sub(self.vaddr.unwrap_or(0));
+        
+        match offset % 4 {
+            0 if ((offset / 4) as usize) < self.len() => Some((offset / 4) as usize),
+            _ => None
+        }
+    }
+    
+    /// The instruction at `addr`.
+    pub fn get(&self, addr: u32) -> Option<&Instruction> {
+        self.index_of(addr).map(|index| &self.instructions[index])
+    }
+    
+    /// Iterates over every instruction along with its address.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &Instruction)> + '_ {
+        self.instructions.iter().enumerate().map(move |(i, instr)| (self.addr_of(i), instr))
+    }
+    
+    /// Copies out the instructions whose addresses are within `range`. Both ends must be word aligned, and may be one
+    /// past the last instruction. Returns `None` if the slice's ROM offset would be past `u32::MAX`.
+    pub fn slice(&self, range: Range<u32>) -> Option<Disassembly> {
+        let boundary = |addr: u32| match self.index_of(addr) {
+            Some(index) => Some(index),
+            None if addr == self.addr_of(self.len()) => Some(self.len()),
+            None => None,
+        };
+        let start = boundary(range.start)?;
+        let end = boundary(range.end)?;
+        
+        if start > end {
+            return None;
+        }
+        
+        let rom_offset = match self.rom_offset {
+            Some(offset) => Some(offset.checked_add(start as u32 * 4)?),
+            None => None,
+        };
+        
+        Some(Disassembly {
+            target: self.target,
+            vaddr: self.vaddr.map(|_| range.start),
+            rom_offset,
+            raw: self.raw[start..end].to_vec(),
+            instructions: self.instructions[start..end].to_vec(),
+        })
+    }
+    
+    /// Virtual address of the word at `offset` in the ROM, if it lies within this block.
+    pub fn rom_to_vaddr(&self, offset: u32) -> Option<u32> {
+        let delta = offset.checked_sub(self.rom_offset?)?;
+        
+        match (delta as usize) < self.len() * 4 {
+            true => Some(self.vaddr?.wrapping_add(delta)),
+            false => None,
+        }
+    }
+    
+    /// ROM offset of the word at `vaddr`, if it lies within this block.
+    pub fn vaddr_to_rom(&self, vaddr: u32) -> Option<u32> {
+        let delta = vaddr.wrapping_sub(self.vaddr?);
+        
+        match (delta as usize) < self.len() * 4 {
+            true => self.rom_offset?.checked_add(delta),
+            false => None,
         }
     }
     
@@ -317,7 +905,7 @@ impl Disassembly {
         
         for (i, instr) in self.all_instructs.iter().enumerate() {
             if instr.op.eq(&op) {
-                if print { println!("[{:#010X}] {}", (i * 4) + 0x40, instr); }
+                if print { println!("[{:#010X}] {}", self.addr_of(i), instr); }
                 
                 results.push((i, instr));
                 
@@ -350,9 +938,9 @@ impl Disassembly {
                 
                 if print {
                     if i == (j as usize) {
-                        println!("{}", format!("[{:#010X}] {}", (j * 4) + 0x40, *instr).green());
+                        println!("{}", format!("[{:#010X}] {}", self.addr_of(j as usize), *instr).green());
                     } else {
-                        println!("[{:#010X}] {}", (j * 4) + 0x40, *instr);
+                        println!("[{:#010X}] {}", self.addr_of(j as usize), *instr);
                     }
                 }
                 
@@ -370,6 +958,13 @@ impl Disassembly {
     }*/
 }
 
+pub fn disassemble_target(code: u32, target: Target) -> Instruction {
+    match target {
+        Target::VR4300 => disassemble(code),
+        Target::RSP => disassemble_rsp(code),
+    }
+}
+
 pub fn disassemble(code: u32) -> Instruction {
     use self::{Operand::*, Operation::*};
     
@@ -385,6 +980,8 @@ pub fn disassemble(code: u32) -> Instruction {
     let base = rs;
     
     let lit16 = (code & 0xFFFF) as u16;
+    let imm16 = lit16 as i16;
+    let target = code & 0x3FFFFFF;
     
     match code {
         0 => Instruction::new0(code, NOP),
@@ -397,10 +994,10 @@ pub fn disassemble(code: u32) -> Instruction {
                 0x06 => Instruction::new3(code, SRLV, Reg(rd), Reg(rt), Reg(rs)),
                 0x07 => Instruction::new3(code, SRAV, Reg(rd), Reg(rt), Reg(rs)),
                 0x08 => Instruction::new1(code, JR, Reg(rs)),
-                0x09 => Instruction::new1(code, JALR, Reg(rs)),
-                0x0C => Instruction::new0(code, SYSCALL),
-                0x0D => Instruction::new0(code, BREAK),
-                0x0F => Instruction::new0(code, SYNC),
+                0x09 => Instruction::new2(code, JALR, Reg(rd), Reg(rs)),
+                0x0C => with_code(Instruction::new0(code, SYSCALL), (code >> 6) & 0xFFFFF),
+                0x0D => break_codes(Instruction::new0(code, BREAK), code),
+                0x0F => with_code(Instruction::new0(code, SYNC), sa as u32),
                 
                 0x10 => Instruction::new1(code, MFHI, Reg(rd)),
                 0x11 => Instruction::new1(code, MTHI, Reg(rs)),
@@ -418,153 +1015,676 @@ pub fn disassemble(code: u32) -> Instruction {
                 0x1E => Instruction::new2(code, DDIV, Reg(rs), Reg(rt)),
                 0x1F => Instruction::new2(code, DDIVU, Reg(rs), Reg(rt)),
                 
-                0x20 => Instruction::new3(code, ADD, Reg(rd), Reg(rt), Reg(rs)),
-                0x21 => Instruction::new3(code, ADDU, Reg(rd), Reg(rt), Reg(rs)),
-                0x22 => Instruction::new3(code, SUB, Reg(rd), Reg(rt), Reg(rs)),
-                0x23 => Instruction::new3(code, SUBU, Reg(rd), Reg(rt), Reg(rs)),
-                0x24 => Instruction::new3(code, AND, Reg(rd), Reg(rt), Reg(rs)),
-                0x25 => Instruction::new3(code, OR, Reg(rd), Reg(rt), Reg(rs)),
-                0x26 => Instruction::new3(code, XOR, Reg(rd), Reg(rt), Reg(rs)),
-                0x27 => Instruction::new3(code, NOR, Reg(rd), Reg(rt), Reg(rs)),
-                0x2A => Instruction::new3(code, SLT, Reg(rd), Reg(rt), Reg(rs)),
-                0x2B => Instruction::new3(code, SLTU, Reg(rd), Reg(rt), Reg(rs)),
-                0x2C => Instruction::new3(code, DADD, Reg(rd), Reg(rt), Reg(rs)),
-                0x2D => Instruction::new3(code, DADDU, Reg(rd), Reg(rt), Reg(rs)),
-                0x2E => Instruction::new3(code, DSUB, Reg(rd), Reg(rt), Reg(rs)),
-                0x2F => Instruction::new3(code, DSUBU, Reg(rd), Reg(rt), Reg(rs)),
+                0x20 => Instruction::new3(code, ADD, Reg(rd), Reg(rs), Reg(rt)),
+                0x21 => Instruction::new3(code, ADDU, Reg(rd), Reg(rs), Reg(rt)),
+                0x22 => Instruction::new3(code, SUB, Reg(rd), Reg(rs), Reg(rt)),
+                0x23 => Instruction::new3(code, SUBU, Reg(rd), Reg(rs), Reg(rt)),
+                0x24 => Instruction::new3(code, AND, Reg(rd), Reg(rs), Reg(rt)),
+                0x25 => Instruction::new3(code, OR, Reg(rd), Reg(rs), Reg(rt)),
+                0x26 => Instruction::new3(code, XOR, Reg(rd), Reg(rs), Reg(rt)),
+                0x27 => Instruction::new3(code, NOR, Reg(rd), Reg(rs), Reg(rt)),
+                0x2A => Instruction::new3(code, SLT, Reg(rd), Reg(rs), Reg(rt)),
+                0x2B => Instruction::new3(code, SLTU, Reg(rd), Reg(rs), Reg(rt)),
+                0x2C => Instruction::new3(code, DADD, Reg(rd), Reg(rs), Reg(rt)),
+                0x2D => Instruction::new3(code, DADDU, Reg(rd), Reg(rs), Reg(rt)),
+                0x2E => Instruction::new3(code, DSUB, Reg(rd), Reg(rs), Reg(rt)),
+                0x2F => Instruction::new3(code, DSUBU, Reg(rd), Reg(rs), Reg(rt)),
                 
-                0x30 => Instruction::new2(code, TGE, Reg(rs), Reg(rt)), //
-                0x31 => Instruction::new2(code, TGEU, Reg(rs), Reg(rt)), //
-                0x32 => Instruction::new2(code, TLT, Reg(rs), Reg(rt)), //
-                0x33 => Instruction::new2(code, TLTU, Reg(rs), Reg(rt)), //
-                0x34 => Instruction::new2(code, TEQ, Reg(rs), Reg(rt)),
-                0x36 => Instruction::new2(code, TNE, Reg(rs), Reg(rt)), //
+                0x30 => with_code(Instruction::new2(code, TGE, Reg(rs), Reg(rt)), (code >> 6) & 0x3FF),
+                0x31 => with_code(Instruction::new2(code, TGEU, Reg(rs), Reg(rt)), (code >> 6) & 0x3FF),
+                0x32 => with_code(Instruction::new2(code, TLT, Reg(rs), Reg(rt)), (code >> 6) & 0x3FF),
+                0x33 => with_code(Instruction::new2(code, TLTU, Reg(rs), Reg(rt)), (code >> 6) & 0x3FF),
+                0x34 => with_code(Instruction::new2(code, TEQ, Reg(rs), Reg(rt)), (code >> 6) & 0x3FF),
+                0x36 => with_code(Instruction::new2(code, TNE, Reg(rs), Reg(rt)), (code >> 6) & 0x3FF),
                 
-                0x38 => Instruction::new3(code, DSLL, Reg(rd), Reg(rt), Reg(rs)),
-                0x3A => Instruction::new3(code, DSRL, Reg(rd), Reg(rt), Reg(rs)),
-                0x3B => Instruction::new3(code, DSRA, Reg(rd), Reg(rt), Reg(rs)),
-                0x3C => Instruction::new3(code, DSLL32, Reg(rd), Reg(rt), Reg(rs)),
-                0x3E => Instruction::new3(code, DSRL32, Reg(rd), Reg(rt), Reg(rs)),
-                0x3F => Instruction::new3(code, DSRA32, Reg(rd), Reg(rt), Reg(rs)),
+                0x38 => Instruction::new3(code, DSLL, Reg(rd), Reg(rt), Lit8(sa)),
+                0x3A => Instruction::new3(code, DSRL, Reg(rd), Reg(rt), Lit8(sa)),
+                0x3B => Instruction::new3(code, DSRA, Reg(rd), Reg(rt), Lit8(sa)),
+                0x3C => Instruction::new3(code, DSLL32, Reg(rd), Reg(rt), Lit8(sa)),
+                0x3E => Instruction::new3(code, DSRL32, Reg(rd), Reg(rt), Lit8(sa)),
+                0x3F => Instruction::new3(code, DSRA32, Reg(rd), Reg(rt), Lit8(sa)),
                 
                 _ => Instruction::new0(code, Unknown)
             },
             0x01 => match vrt {
-                0x00 => Instruction::new2(code, BLTZ, Reg(rs), Lit16(lit16)),
-                0x01 => Instruction::new2(code, BGEZ, Reg(rs), Lit16(lit16)),
-                0x02 => Instruction::new2(code, BLTZL, Reg(rs), Lit16(lit16)),
-                0x03 => Instruction::new2(code, BGEZL, Reg(rs), Lit16(lit16)),
+                0x00 => Instruction::new2(code, BLTZ, Reg(rs), Branch(lit16 as i16)),
+                0x01 => Instruction::new2(code, BGEZ, Reg(rs), Branch(lit16 as i16)),
+                0x02 => Instruction::new2(code, BLTZL, Reg(rs), Branch(lit16 as i16)),
+                0x03 => Instruction::new2(code, BGEZL, Reg(rs), Branch(lit16 as i16)),
                 
-                0x08 => Instruction::new2(code, TGEI, Reg(rs), Lit16(lit16)), //
-                0x09 => Instruction::new2(code, TGEIU, Reg(rs), Lit16(lit16)), //
-                0x0A => Instruction::new2(code, TLTI, Reg(rs), Lit16(lit16)), //
-                0x0B => Instruction::new2(code, TLTIU, Reg(rs), Lit16(lit16)), //
-                0x0C => Instruction::new2(code, TEQI, Reg(rs), Lit16(lit16)), //
-                0x0E => Instruction::new2(code, TNEI, Reg(rs), Lit16(lit16)), //
+                0x08 => Instruction::new2(code, TGEI, Reg(rs), Imm16(imm16)), //
+                0x09 => Instruction::new2(code, TGEIU, Reg(rs), Imm16(imm16)), //
+                0x0A => Instruction::new2(code, TLTI, Reg(rs), Imm16(imm16)), //
+                0x0B => Instruction::new2(code, TLTIU, Reg(rs), Imm16(imm16)), //
+                0x0C => Instruction::new2(code, TEQI, Reg(rs), Imm16(imm16)), //
+                0x0E => Instruction::new2(code, TNEI, Reg(rs), Imm16(imm16)), //
                 
-                0x10 => Instruction::new2(code, BLTZAL, Reg(rs), Lit16(lit16)),
-                0x11 => Instruction::new2(code, BGEZAL, Reg(rs), Lit16(lit16)),
-                0x12 => Instruction::new2(code, BLTZALL, Reg(rs), Lit16(lit16)),
-                0x13 => Instruction::new2(code, BGEZALL, Reg(rs), Lit16(lit16)),
+                0x10 => Instruction::new2(code, BLTZAL, Reg(rs), Branch(lit16 as i16)),
+                0x11 => Instruction::new2(code, BGEZAL, Reg(rs), Branch(lit16 as i16)),
+                0x12 => Instruction::new2(code, BLTZALL, Reg(rs), Branch(lit16 as i16)),
+                0x13 => Instruction::new2(code, BGEZALL, Reg(rs), Branch(lit16 as i16)),
                 
                 _ => Instruction::new0(code, Unknown)
             },
-            0x02 => Instruction::new1(code, J, Lit16(lit16)),
-            0x03 => Instruction::new1(code, JAL, Lit16(lit16)),
-            0x04 => Instruction::new3(code, BEQ, Reg(rs), Reg(rt), Lit16(lit16)),
-            0x05 => Instruction::new3(code, BNE, Reg(rs), Reg(rt), Lit16(lit16)),
-            0x06 => Instruction::new2(code, BLEZ, Reg(rs), Lit16(lit16)),
-            0x07 => Instruction::new2(code, BGTZ, Reg(rs), Lit16(lit16)),
-            0x08 => Instruction::new3(code, ADDI, Reg(rt), Reg(rs), Lit16(lit16)),
-            0x09 => Instruction::new3(code, ADDIU, Reg(rt), Reg(rs), Lit16(lit16)),
-            0x0A => Instruction::new3(code, SLTI, Reg(rt), Reg(rs), Lit16(lit16)),
-            0x0B => Instruction::new3(code, SLTIU, Reg(rt), Reg(rs), Lit16(lit16)),
+            0x02 => Instruction::new1(code, J, JumpTarget(target)),
+            0x03 => Instruction::new1(code, JAL, JumpTarget(target)),
+            0x04 => Instruction::new3(code, BEQ, Reg(rs), Reg(rt), Branch(lit16 as i16)),
+            0x05 => Instruction::new3(code, BNE, Reg(rs), Reg(rt), Branch(lit16 as i16)),
+            0x06 => Instruction::new2(code, BLEZ, Reg(rs), Branch(lit16 as i16)),
+            0x07 => Instruction::new2(code, BGTZ, Reg(rs), Branch(lit16 as i16)),
+            0x08 => Instruction::new3(code, ADDI, Reg(rt), Reg(rs), Imm16(imm16)),
+            0x09 => Instruction::new3(code, ADDIU, Reg(rt), Reg(rs), Imm16(imm16)),
+            0x0A => Instruction::new3(code, SLTI, Reg(rt), Reg(rs), Imm16(imm16)),
+            0x0B => Instruction::new3(code, SLTIU, Reg(rt), Reg(rs), Imm16(imm16)),
             0x0C => Instruction::new3(code, ANDI, Reg(rt), Reg(rs), Lit16(lit16)),
             0x0D => Instruction::new3(code, ORI, Reg(rt), Reg(rs), Lit16(lit16)),
             0x0E => Instruction::new3(code, XORI, Reg(rt), Reg(rs), Lit16(lit16)),
             0x0F => Instruction::new2(code, LUI, Reg(rt), Lit16(lit16)),
             
+            0x11 => disassemble_cop1(code),
             0x10..=0x13 => match (code >> 21) & 0x1F {
                 0x00 if cpz == 0 => Instruction::new2(code, MFC0, Reg(rt), Cp0Reg(rd)),
-                0x00 => Instruction::new2(code, MFCz, Reg(rt), Reg(rd)),
+                0x00 => Instruction::new2(code, MFCz, Reg(rt), CopReg(rd)),
                 
                 0x01 if cpz == 0 => Instruction::new2(code, DMFC0, Reg(rt), Cp0Reg(rd)),
                 
-                0x02 => Instruction::new2(code, CFCz, Reg(rt), Reg(rd)),
+                0x02 => Instruction::new2(code, CFCz, Reg(rt), CopReg(rd)),
                 
                 0x04 if cpz == 0 => Instruction::new2(code, MTC0, Reg(rt), Cp0Reg(rd)),
-                0x04 => Instruction::new2(code, MTCz, Reg(rt), Reg(rd)),
+                0x04 => Instruction::new2(code, MTCz, Reg(rt), CopReg(rd)),
                 
                 0x05 if cpz == 0 => Instruction::new2(code, DMTC0, Reg(rt), Cp0Reg(rd)),
                 
-                0x06 => Instruction::new2(code, CTCz, Reg(rt), Reg(rd)),
+                0x06 => Instruction::new2(code, CTCz, Reg(rt), CopReg(rd)),
                 
                 0x08 => match (code >> 16) & 0x1F {
-                    0x00 => Instruction::new1(code, BCzF, Lit16(lit16)),
-                    0x01 => Instruction::new1(code, BCzFL, Lit16(lit16)),
-                    0x02 => Instruction::new1(code, BCzT, Lit16(lit16)),
-                    0x03 => Instruction::new1(code, BCzTL, Lit16(lit16)),
+                    0x00 => Instruction::new1(code, BCzF, Branch(lit16 as i16)),
+                    0x01 => Instruction::new1(code, BCzT, Branch(lit16 as i16)),
+                    0x02 => Instruction::new1(code, BCzFL, Branch(lit16 as i16)),
+                    0x03 => Instruction::new1(code, BCzTL, Branch(lit16 as i16)),
                     _ => Instruction::new0(code, Unknown)
                 },
                 
-                0x10 if special == 0x01 => Instruction::new0(code, TLBR),
-                0x10 if special == 0x02 => Instruction::new0(code, TLBWI),
-                0x10 if special == 0x06 => Instruction::new0(code, TLBWR),
-                0x10 if special == 0x08 => Instruction::new0(code, TLBP),
-                0x10 if special == 0x18 => Instruction::new0(code, ERET),
+                0x10 if cpz == 0 && special == 0x01 => Instruction::new0(code, TLBR),
+                0x10 if cpz == 0 && special == 0x02 => Instruction::new0(code, TLBWI),
+                0x10 if cpz == 0 && special == 0x06 => Instruction::new0(code, TLBWR),
+                0x10 if cpz == 0 && special == 0x08 => Instruction::new0(code, TLBP),
+                0x10 if cpz == 0 && special == 0x18 => Instruction::new0(code, ERET),
                 0x10..=0x1F => Instruction::new1(code, COPz, Lit32(code & 0x1FFFFFF)),
                 
                 _ => Instruction::new0(code, Unknown)
             },
             
-            0x14 => Instruction::new3(code, BEQL, Reg(rs), Reg(rt), Lit16(lit16)),
-            0x15 => Instruction::new3(code, BNEL, Reg(rs), Reg(rt), Lit16(lit16)),
-            0x16 => Instruction::new2(code, BLEZL, Reg(rs), Lit16(lit16)),
-            0x17 => Instruction::new2(code, BGTZL, Reg(rs), Lit16(lit16)),
-            0x18 => Instruction::new3(code, DADDI, Reg(rt), Reg(rs), Lit16(lit16)),
-            0x19 => Instruction::new3(code, DADDIU, Reg(rt), Reg(rs), Lit16(lit16)),
+            0x14 => Instruction::new3(code, BEQL, Reg(rs), Reg(rt), Branch(lit16 as i16)),
+            0x15 => Instruction::new3(code, BNEL, Reg(rs), Reg(rt), Branch(lit16 as i16)),
+            0x16 => Instruction::new2(code, BLEZL, Reg(rs), Branch(lit16 as i16)),
+            0x17 => Instruction::new2(code, BGTZL, Reg(rs), Branch(lit16 as i16)),
+            0x18 => Instruction::new3(code, DADDI, Reg(rt), Reg(rs), Imm16(imm16)),
+            0x19 => Instruction::new3(code, DADDIU, Reg(rt), Reg(rs), Imm16(imm16)),
             
-            0x1A => Instruction::new3(code, LDL, Reg(rt), Reg(base), Lit16(lit16)),
-            0x1B => Instruction::new3(code, LDR, Reg(rt), Reg(base), Lit16(lit16)),
+            0x1A => Instruction::new2(code, LDL, Reg(rt), Mem(base, imm16)),
+            0x1B => Instruction::new2(code, LDR, Reg(rt), Mem(base, imm16)),
             
-            0x20 => Instruction::new3(code, LB, Reg(rt), Reg(base), Lit16(lit16)),
-            0x21 => Instruction::new3(code, LH, Reg(rt), Reg(base), Lit16(lit16)),
-            0x22 => Instruction::new3(code, LWL, Reg(rt), Reg(base), Lit16(lit16)),
-            0x23 => Instruction::new3(code, LW, Reg(rt), Reg(base), Lit16(lit16)),
-            0x24 => Instruction::new3(code, LBU, Reg(rt), Reg(base), Lit16(lit16)),
-            0x25 => Instruction::new3(code, LHU, Reg(rt), Reg(base), Lit16(lit16)),
-            0x26 => Instruction::new3(code, LWR, Reg(rt), Reg(base), Lit16(lit16)),
-            0x27 => Instruction::new3(code, LWU, Reg(rt), Reg(base), Lit16(lit16)),
-            0x28 => Instruction::new3(code, SB, Reg(rt), Reg(base), Lit16(lit16)),
-            0x29 => Instruction::new3(code, SH, Reg(rt), Reg(base), Lit16(lit16)),
-            0x2A => Instruction::new3(code, SWL, Reg(rt), Reg(base), Lit16(lit16)),
-            0x2B => Instruction::new3(code, SW, Reg(rt), Reg(base), Lit16(lit16)),
-            0x2C => Instruction::new3(code, SDL, Reg(rt), Reg(base), Lit16(lit16)),
-            0x2D => Instruction::new3(code, SDR, Reg(rt), Reg(base), Lit16(lit16)),
-            0x2E => Instruction::new3(code, SWR, Reg(rt), Reg(base), Lit16(lit16)),
-            0x2F => Instruction::new0(code, CACHE),
+            0x20 => Instruction::new2(code, LB, Reg(rt), Mem(base, imm16)),
+            0x21 => Instruction::new2(code, LH, Reg(rt), Mem(base, imm16)),
+            0x22 => Instruction::new2(code, LWL, Reg(rt), Mem(base, imm16)),
+            0x23 => Instruction::new2(code, LW, Reg(rt), Mem(base, imm16)),
+            0x24 => Instruction::new2(code, LBU, Reg(rt), Mem(base, imm16)),
+            0x25 => Instruction::new2(code, LHU, Reg(rt), Mem(base, imm16)),
+            0x26 => Instruction::new2(code, LWR, Reg(rt), Mem(base, imm16)),
+            0x27 => Instruction::new2(code, LWU, Reg(rt), Mem(base, imm16)),
+            0x28 => Instruction::new2(code, SB, Reg(rt), Mem(base, imm16)),
+            0x29 => Instruction::new2(code, SH, Reg(rt), Mem(base, imm16)),
+            0x2A => Instruction::new2(code, SWL, Reg(rt), Mem(base, imm16)),
+            0x2B => Instruction::new2(code, SW, Reg(rt), Mem(base, imm16)),
+            0x2C => Instruction::new2(code, SDL, Reg(rt), Mem(base, imm16)),
+            0x2D => Instruction::new2(code, SDR, Reg(rt), Mem(base, imm16)),
+            0x2E => Instruction::new2(code, SWR, Reg(rt), Mem(base, imm16)),
+            0x2F => Instruction::new2(code, CACHE, CacheOp(rt), Mem(base, imm16)),
             
-            0x30 => Instruction::new3(code, LL, Reg(rt), Reg(base), Lit16(lit16)),
-            0x31 | 0x32 => Instruction::new3(code, LWCz, Reg(rt), Reg(base), Lit16(lit16)),
-            0x34 => Instruction::new3(code, LLD, Reg(rt), Reg(base), Lit16(lit16)),
-            0x35 | 0x36 => Instruction::new3(code, LDCz, Reg(rt), Reg(base), Lit16(lit16)),
-            0x37 => Instruction::new3(code, LD, Reg(rt), Reg(base), Lit16(lit16)),
+            0x30 => Instruction::new2(code, LL, Reg(rt), Mem(base, imm16)),
+            0x31 => Instruction::new2(code, LWC1, Cp1Reg(rt), Mem(base, imm16)),
+            0x32 => Instruction::new2(code, LWCz, CopReg(rt), Mem(base, imm16)),
+            0x34 => Instruction::new2(code, LLD, Reg(rt), Mem(base, imm16)),
+            0x35 => Instruction::new2(code, LDC1, Cp1Reg(rt), Mem(base, imm16)),
+            0x36 => Instruction::new2(code, LDCz, CopReg(rt), Mem(base, imm16)),
+            0x37 => Instruction::new2(code, LD, Reg(rt), Mem(base, imm16)),
             
-            0x38 => Instruction::new3(code, SC, Reg(rt), Reg(base), Lit16(lit16)),
-            0x39 | 0x3A => Instruction::new3(code, SWCz, Reg(rt), Reg(base), Lit16(lit16)),
-            0x3B => Instruction::new3(code, SCD, Reg(rt), Reg(base), Lit16(lit16)),
-            0x3C | 0x3D => Instruction::new3(code, SDCz, Reg(rt), Reg(base), Lit16(lit16)),
-            0x3F => Instruction::new3(code, SD, Reg(rt), Reg(base), Lit16(lit16)),
+            0x38 => Instruction::new2(code, SC, Reg(rt), Mem(base, imm16)),
+            0x39 => Instruction::new2(code, SWC1, Cp1Reg(rt), Mem(base, imm16)),
+            0x3A => Instruction::new2(code, SWCz, CopReg(rt), Mem(base, imm16)),
+            0x3C => Instruction::new2(code, SCD, Reg(rt), Mem(base, imm16)),
+            0x3D => Instruction::new2(code, SDC1, Cp1Reg(rt), Mem(base, imm16)),
+            0x3E => Instruction::new2(code, SDCz, CopReg(rt), Mem(base, imm16)),
+            0x3F => Instruction::new2(code, SD, Reg(rt), Mem(base, imm16)),
             
             _ => Instruction::new0(code, Unknown)
         },
     }
 }
 
+fn disassemble_cop1(code: u32) -> Instruction {
+    use self::{Operand::*, Operation::*};
+    
+    let fmt = (code >> 21) & 0x1F;
+    let funct = code & 0x3F;
+    
+    let rt = ((code >> 16) & 0x1F) as u8;
+    let ft = ((code >> 16) & 0x1F) as u8;
+    let fs = ((code >> 11) & 0x1F) as u8;
+    let fd = ((code >> 6) & 0x1F) as u8;
+    
+    let lit16 = (code & 0xFFFF) as u16;
+    
+    let single = fmt == 0x10;
+    let pick = |s: Operation, d: Operation| if single { s } else { d };
+    
+    match fmt {
+        0x00 => Instruction::new2(code, MFC1, Reg(rt), Cp1Reg(fs)),
+        0x01 => Instruction::new2(code, DMFC1, Reg(rt), Cp1Reg(fs)),
+        0x02 => Instruction::new2(code, CFC1, Reg(rt), Cp1CtrlReg(fs)),
+        0x04 => Instruction::new2(code, MTC1, Reg(rt), Cp1Reg(fs)),
+        0x05 => Instruction::new2(code, DMTC1, Reg(rt), Cp1Reg(fs)),
+        0x06 => Instruction::new2(code, CTC1, Reg(rt), Cp1CtrlReg(fs)),
+        
+        0x08 => match (code >> 16) & 0x1F {
+            0x00 => Instruction::new1(code, BC1F, Branch(lit16 as i16)),
+            0x01 => Instruction::new1(code, BC1T, Branch(lit16 as i16)),
+            0x02 => Instruction::new1(code, BC1FL, Branch(lit16 as i16)),
+            0x03 => Instruction::new1(code, BC1TL, Branch(lit16 as i16)),
+            _ => Instruction::new0(code, Unknown)
+        },
+        
+        0x10 | 0x11 => match funct {
+            0x00 => Instruction::new3(code, pick(ADD_S, ADD_D), Cp1Reg(fd), Cp1Reg(fs), Cp1Reg(ft)),
+            0x01 => Instruction::new3(code, pick(SUB_S, SUB_D), Cp1Reg(fd), Cp1Reg(fs), Cp1Reg(ft)),
+            0x02 => Instruction::new3(code, pick(MUL_S, MUL_D), Cp1Reg(fd), Cp1Reg(fs), Cp1Reg(ft)),
+            0x03 => Instruction::new3(code, pick(DIV_S, DIV_D), Cp1Reg(fd), Cp1Reg(fs), Cp1Reg(ft)),
+            
+            0x04 => Instruction::new2(code, pick(SQRT_S, SQRT_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x05 => Instruction::new2(code, pick(ABS_S, ABS_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x06 => Instruction::new2(code, pick(MOV_S, MOV_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x07 => Instruction::new2(code, pick(NEG_S, NEG_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x08 => Instruction::new2(code, pick(ROUND_L_S, ROUND_L_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x09 => Instruction::new2(code, pick(TRUNC_L_S, TRUNC_L_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x0A => Instruction::new2(code, pick(CEIL_L_S, CEIL_L_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x0B => Instruction::new2(code, pick(FLOOR_L_S, FLOOR_L_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x0C => Instruction::new2(code, pick(ROUND_W_S, ROUND_W_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x0D => Instruction::new2(code, pick(TRUNC_W_S, TRUNC_W_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x0E => Instruction::new2(code, pick(CEIL_W_S, CEIL_W_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x0F => Instruction::new2(code, pick(FLOOR_W_S, FLOOR_W_D), Cp1Reg(fd), Cp1Reg(fs)),
+            
+            0x20 if !single => Instruction::new2(code, CVT_S_D, Cp1Reg(fd), Cp1Reg(fs)),
+            0x21 if single => Instruction::new2(code, CVT_D_S, Cp1Reg(fd), Cp1Reg(fs)),
+            0x24 => Instruction::new2(code, pick(CVT_W_S, CVT_W_D), Cp1Reg(fd), Cp1Reg(fs)),
+            0x25 => Instruction::new2(code, pick(CVT_L_S, CVT_L_D), Cp1Reg(fd), Cp1Reg(fs)),
+            
+            0x30 => Instruction::new2(code, pick(C_F_S, C_F_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x31 => Instruction::new2(code, pick(C_UN_S, C_UN_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x32 => Instruction::new2(code, pick(C_EQ_S, C_EQ_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x33 => Instruction::new2(code, pick(C_UEQ_S, C_UEQ_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x34 => Instruction::new2(code, pick(C_OLT_S, C_OLT_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x35 => Instruction::new2(code, pick(C_ULT_S, C_ULT_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x36 => Instruction::new2(code, pick(C_OLE_S, C_OLE_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x37 => Instruction::new2(code, pick(C_ULE_S, C_ULE_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x38 => Instruction::new2(code, pick(C_SF_S, C_SF_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x39 => Instruction::new2(code, pick(C_NGLE_S, C_NGLE_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x3A => Instruction::new2(code, pick(C_SEQ_S, C_SEQ_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x3B => Instruction::new2(code, pick(C_NGL_S, C_NGL_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x3C => Instruction::new2(code, pick(C_LT_S, C_LT_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x3D => Instruction::new2(code, pick(C_NGE_S, C_NGE_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x3E => Instruction::new2(code, pick(C_LE_S, C_LE_D), Cp1Reg(fs), Cp1Reg(ft)),
+            0x3F => Instruction::new2(code, pick(C_NGT_S, C_NGT_D), Cp1Reg(fs), Cp1Reg(ft)),
+            
+            _ => Instruction::new0(code, Unknown)
+        },
+        
+        0x14 => match funct {
+            0x20 => Instruction::new2(code, CVT_S_W, Cp1Reg(fd), Cp1Reg(fs)),
+            0x21 => Instruction::new2(code, CVT_D_W, Cp1Reg(fd), Cp1Reg(fs)),
+            _ => Instruction::new0(code, Unknown)
+        },
+        0x15 => match funct {
+            0x20 => Instruction::new2(code, CVT_S_L, Cp1Reg(fd), Cp1Reg(fs)),
+            0x21 => Instruction::new2(code, CVT_D_L, Cp1Reg(fd), Cp1Reg(fs)),
+            _ => Instruction::new0(code, Unknown)
+        },
+        
+        _ => Instruction::new0(code, Unknown)
+    }
+}
+
+/// Appends the code field of a SYSCALL/BREAK/SYNC/trap instruction as an operand, unless it is zero.
+fn with_code(mut instr: Instruction, field: u32) -> Instruction {
+    if field != 0 {
+        if let Some(slot) = instr.args.iter_mut().find(|arg| arg.is_none()) {
+            *slot = Some(Operand::Code(field));
+        }
+    }
+    
+    instr
+}
+
+/// Appends the two codes of a BREAK instruction the way GNU as takes them: `break code1` for bits 25..16, and
+/// `break code1, code2` once bits 15..6 are set too.
+fn break_codes(instr: Instruction, code: u32) -> Instruction {
+    let (code1, code2) = ((code >> 16) & 0x3FF, (code >> 6) & 0x3FF);
+    
+    match code2 {
+        0 => with_code(instr, code1),
+        _ => Instruction::new2(instr.code, instr.op, Operand::Code(code1), Operand::Code(code2)),
+    }
+}
+
+pub fn disassemble_at(code: u32, pc: u32) -> Instruction {
+    let mut instr = disassemble(code);
+    instr.resolve(pc, Target::VR4300);
+    
+    instr
+}
+
 pub fn disassemble_u8(code_parts: &[u8]) -> Instruction {
     disassemble(((code_parts[0] as u32) << 24) | ((code_parts[1] as u32) << 16) | ((code_parts[2] as u32) << 8) | (code_parts[3] as u32))
 }
 
 fn to_u32(bytes: &[u8]) -> u32 {
     ((bytes[0] as u32) << 24) | ((bytes[1] as u32) << 16) | ((bytes[2] as u32) << 8) | (bytes[3] as u32)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{disassemble_target, Disassembly, Operand, Operand::*, Operation, Operation::*, Target, Target::*};
+    use strum::IntoEnumIterator;
+    
+    /// Known encodings for every operation, with the exact operand list the decoder is expected to produce.
+    pub(crate) const ENCODINGS: &[(Target, u32, Operation, &[Operand])] = &[
+        (VR4300, 0x00052100, SLL, &[Reg(4), Reg(5), Lit8(4)]),
+        (VR4300, 0x000527C2, SRL, &[Reg(4), Reg(5), Lit8(31)]),
+        (VR4300, 0x00052043, SRA, &[Reg(4), Reg(5), Lit8(1)]),
+        (VR4300, 0x00C52004, SLLV, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00C52006, SRLV, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00C52007, SRAV, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00C52014, DSLLV, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00C52016, DSRLV, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00C52017, DSRAV, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x03E00008, JR, &[Reg(31)]),
+        (VR4300, 0x0320F809, JALR, &[Reg(31), Reg(25)]),
+        (VR4300, 0x0000000C, SYSCALL, &[]),
+        (VR4300, 0x0007000D, BREAK, &[Code(7)]),
+        (VR4300, 0x00048D0D, BREAK, &[Code(4), Code(0x234)]),
+        (VR4300, 0x0000000F, SYNC, &[]),
+        (VR4300, 0x00001010, MFHI, &[Reg(2)]),
+        (VR4300, 0x00400011, MTHI, &[Reg(2)]),
+        (VR4300, 0x00001012, MFLO, &[Reg(2)]),
+        (VR4300, 0x00400013, MTLO, &[Reg(2)]),
+        (VR4300, 0x00850018, MULT, &[Reg(4), Reg(5)]),
+        (VR4300, 0x00850019, MULTU, &[Reg(4), Reg(5)]),
+        (VR4300, 0x0085001A, DIV, &[Reg(4), Reg(5)]),
+        (VR4300, 0x0085001B, DIVU, &[Reg(4), Reg(5)]),
+        (VR4300, 0x0085001C, DMULT, &[Reg(4), Reg(5)]),
+        (VR4300, 0x0085001D, DMULTU, &[Reg(4), Reg(5)]),
+        (VR4300, 0x0085001E, DDIV, &[Reg(4), Reg(5)]),
+        (VR4300, 0x0085001F, DDIVU, &[Reg(4), Reg(5)]),
+        (VR4300, 0x00A62020, ADD, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A62021, ADDU, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A62022, SUB, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A62023, SUBU, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A62024, AND, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A62025, OR, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A62026, XOR, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A62027, NOR, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A6202A, SLT, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A6202B, SLTU, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A6202C, DADD, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A6202D, DADDU, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A6202E, DSUB, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x00A6202F, DSUBU, &[Reg(4), Reg(5), Reg(6)]),
+        (VR4300, 0x008501F0, TGE, &[Reg(4), Reg(5), Code(7)]),
+        (VR4300, 0x008501F1, TGEU, &[Reg(4), Reg(5), Code(7)]),
+        (VR4300, 0x008501F2, TLT, &[Reg(4), Reg(5), Code(7)]),
+        (VR4300, 0x008501F3, TLTU, &[Reg(4), Reg(5), Code(7)]),
+        (VR4300, 0x008501F4, TEQ, &[Reg(4), Reg(5), Code(7)]),
+        (VR4300, 0x008501F6, TNE, &[Reg(4), Reg(5), Code(7)]),
+        (VR4300, 0x000520F8, DSLL, &[Reg(4), Reg(5), Lit8(3)]),
+        (VR4300, 0x000520FA, DSRL, &[Reg(4), Reg(5), Lit8(3)]),
+        (VR4300, 0x000520FB, DSRA, &[Reg(4), Reg(5), Lit8(3)]),
+        (VR4300, 0x000520FC, DSLL32, &[Reg(4), Reg(5), Lit8(3)]),
+        (VR4300, 0x000520FE, DSRL32, &[Reg(4), Reg(5), Lit8(3)]),
+        (VR4300, 0x000520FF, DSRA32, &[Reg(4), Reg(5), Lit8(3)]),
+        (VR4300, 0x0480FFFC, BLTZ, &[Reg(4), Branch(-4)]),
+        (VR4300, 0x0481FFFC, BGEZ, &[Reg(4), Branch(-4)]),
+        (VR4300, 0x0482FFFC, BLTZL, &[Reg(4), Branch(-4)]),
+        (VR4300, 0x0483FFFC, BGEZL, &[Reg(4), Branch(-4)]),
+        (VR4300, 0x0490FFFC, BLTZAL, &[Reg(4), Branch(-4)]),
+        (VR4300, 0x0491FFFC, BGEZAL, &[Reg(4), Branch(-4)]),
+        (VR4300, 0x0492FFFC, BLTZALL, &[Reg(4), Branch(-4)]),
+        (VR4300, 0x0493FFFC, BGEZALL, &[Reg(4), Branch(-4)]),
+        (VR4300, 0x0488FFFE, TGEI, &[Reg(4), Imm16(-2)]),
+        (VR4300, 0x0489FFFE, TGEIU, &[Reg(4), Imm16(-2)]),
+        (VR4300, 0x048AFFFE, TLTI, &[Reg(4), Imm16(-2)]),
+        (VR4300, 0x048BFFFE, TLTIU, &[Reg(4), Imm16(-2)]),
+        (VR4300, 0x048CFFFE, TEQI, &[Reg(4), Imm16(-2)]),
+        (VR4300, 0x048EFFFE, TNEI, &[Reg(4), Imm16(-2)]),
+        (VR4300, 0x0BFFFFFF, J, &[JumpTarget(0x3FFFFFF)]),
+        (VR4300, 0x0C004D0D, JAL, &[JumpTarget(0x0004D0D)]),
+        (VR4300, 0x10850010, BEQ, &[Reg(4), Reg(5), Branch(16)]),
+        (VR4300, 0x14850010, BNE, &[Reg(4), Reg(5), Branch(16)]),
+        (VR4300, 0x50850010, BEQL, &[Reg(4), Reg(5), Branch(16)]),
+        (VR4300, 0x54850010, BNEL, &[Reg(4), Reg(5), Branch(16)]),
+        (VR4300, 0x18807FFF, BLEZ, &[Reg(4), Branch(32767)]),
+        (VR4300, 0x1C807FFF, BGTZ, &[Reg(4), Branch(32767)]),
+        (VR4300, 0x58807FFF, BLEZL, &[Reg(4), Branch(32767)]),
+        (VR4300, 0x5C807FFF, BGTZL, &[Reg(4), Branch(32767)]),
+        (VR4300, 0x23BDFFE8, ADDI, &[Reg(29), Reg(29), Imm16(-0x18)]),
+        (VR4300, 0x27BDFFE8, ADDIU, &[Reg(29), Reg(29), Imm16(-0x18)]),
+        (VR4300, 0x2BBDFFE8, SLTI, &[Reg(29), Reg(29), Imm16(-0x18)]),
+        (VR4300, 0x2FBDFFE8, SLTIU, &[Reg(29), Reg(29), Imm16(-0x18)]),
+        (VR4300, 0x63BDFFE8, DADDI, &[Reg(29), Reg(29), Imm16(-0x18)]),
+        (VR4300, 0x67BDFFE8, DADDIU, &[Reg(29), Reg(29), Imm16(-0x18)]),
+        (VR4300, 0x30A48001, ANDI, &[Reg(4), Reg(5), Lit16(0x8001)]),
+        (VR4300, 0x34A48001, ORI, &[Reg(4), Reg(5), Lit16(0x8001)]),
+        (VR4300, 0x38A48001, XORI, &[Reg(4), Reg(5), Lit16(0x8001)]),
+        (VR4300, 0x3C048000, LUI, &[Reg(4), Lit16(0x8000)]),
+        (VR4300, 0x40086000, MFC0, &[Reg(8), Cp0Reg(12)]),
+        (VR4300, 0x40287000, DMFC0, &[Reg(8), Cp0Reg(14)]),
+        (VR4300, 0x40886800, MTC0, &[Reg(8), Cp0Reg(13)]),
+        (VR4300, 0x40A8F000, DMTC0, &[Reg(8), Cp0Reg(30)]),
+        (VR4300, 0x42000001, TLBR, &[]),
+        (VR4300, 0x42000002, TLBWI, &[]),
+        (VR4300, 0x42000006, TLBWR, &[]),
+        (VR4300, 0x42000008, TLBP, &[]),
+        (VR4300, 0x42000018, ERET, &[]),
+        (VR4300, 0x48082800, MFCz, &[Reg(8), CopReg(5)]),
+        (VR4300, 0x48482800, CFCz, &[Reg(8), CopReg(5)]),
+        (VR4300, 0x48882800, MTCz, &[Reg(8), CopReg(5)]),
+        (VR4300, 0x48C82800, CTCz, &[Reg(8), CopReg(5)]),
+        (VR4300, 0x4900FFFF, BCzF, &[Branch(-1)]),
+        (VR4300, 0x4901FFFF, BCzT, &[Branch(-1)]),
+        (VR4300, 0x4902FFFF, BCzFL, &[Branch(-1)]),
+        (VR4300, 0x4903FFFF, BCzTL, &[Branch(-1)]),
+        (VR4300, 0x4A123456, COPz, &[Lit32(0x123456)]),
+        (VR4300, 0x4408F800, MFC1, &[Reg(8), Cp1Reg(31)]),
+        (VR4300, 0x4428F000, DMFC1, &[Reg(8), Cp1Reg(30)]),
+        (VR4300, 0x4448F800, CFC1, &[Reg(8), Cp1CtrlReg(31)]),
+        (VR4300, 0x44881000, MTC1, &[Reg(8), Cp1Reg(2)]),
+        (VR4300, 0x44A82000, DMTC1, &[Reg(8), Cp1Reg(4)]),
+        (VR4300, 0x44C8F800, CTC1, &[Reg(8), Cp1CtrlReg(31)]),
+        (VR4300, 0x45000002, BC1F, &[Branch(2)]),
+        (VR4300, 0x45010002, BC1T, &[Branch(2)]),
+        (VR4300, 0x45020002, BC1FL, &[Branch(2)]),
+        (VR4300, 0x45030002, BC1TL, &[Branch(2)]),
+        (VR4300, 0x46062080, ADD_S, &[Cp1Reg(2), Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262080, ADD_D, &[Cp1Reg(2), Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062081, SUB_S, &[Cp1Reg(2), Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262081, SUB_D, &[Cp1Reg(2), Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062082, MUL_S, &[Cp1Reg(2), Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262082, MUL_D, &[Cp1Reg(2), Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062083, DIV_S, &[Cp1Reg(2), Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262083, DIV_D, &[Cp1Reg(2), Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46002084, SQRT_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46202084, SQRT_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46002085, ABS_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46202085, ABS_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46002086, MOV_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46202086, MOV_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46002087, NEG_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46202087, NEG_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46002088, ROUND_L_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46202088, ROUND_L_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46002089, TRUNC_L_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46202089, TRUNC_L_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4600208A, CEIL_L_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4620208A, CEIL_L_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4600208B, FLOOR_L_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4620208B, FLOOR_L_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4600208C, ROUND_W_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4620208C, ROUND_W_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4600208D, TRUNC_W_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4620208D, TRUNC_W_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4600208E, CEIL_W_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4620208E, CEIL_W_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4600208F, FLOOR_W_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x4620208F, FLOOR_W_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x460020A4, CVT_W_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x462020A4, CVT_W_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x460020A5, CVT_L_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x462020A5, CVT_L_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x462020A0, CVT_S_D, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x460020A1, CVT_D_S, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x468020A0, CVT_S_W, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x468020A1, CVT_D_W, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46A020A0, CVT_S_L, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46A020A1, CVT_D_L, &[Cp1Reg(2), Cp1Reg(4)]),
+        (VR4300, 0x46062030, C_F_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262030, C_F_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062031, C_UN_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262031, C_UN_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062032, C_EQ_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262032, C_EQ_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062033, C_UEQ_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262033, C_UEQ_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062034, C_OLT_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262034, C_OLT_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062035, C_ULT_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262035, C_ULT_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062036, C_OLE_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262036, C_OLE_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062037, C_ULE_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262037, C_ULE_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062038, C_SF_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262038, C_SF_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46062039, C_NGLE_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x46262039, C_NGLE_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4606203A, C_SEQ_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4626203A, C_SEQ_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4606203B, C_NGL_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4626203B, C_NGL_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4606203C, C_LT_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4626203C, C_LT_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4606203D, C_NGE_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4626203D, C_NGE_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4606203E, C_LE_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4626203E, C_LE_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4606203F, C_NGT_S, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x4626203F, C_NGT_D, &[Cp1Reg(4), Cp1Reg(6)]),
+        (VR4300, 0x6BBF0014, LDL, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0x6FBF0014, LDR, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0x83BF0014, LB, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0x87BF0014, LH, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0x8BBF0014, LWL, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0x8FBF0014, LW, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0x93BF0014, LBU, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0x97BF0014, LHU, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0x9BBF0014, LWR, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0x9FBF0014, LWU, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xA3BF0014, SB, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xA7BF0014, SH, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xABBF0014, SWL, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xAFBF0014, SW, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xB3BF0014, SDL, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xB7BF0014, SDR, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xBBBF0014, SWR, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xC3BF0014, LL, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xD3BF0014, LLD, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xDFBF0014, LD, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xE3BF0014, SC, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xF3BF0014, SCD, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xFFBF0014, SD, &[Reg(31), Mem(29, 0x14)]),
+        (VR4300, 0xC7B4FFF8, LWC1, &[Cp1Reg(20), Mem(29, -8)]),
+        (VR4300, 0xD7B4FFF8, LDC1, &[Cp1Reg(20), Mem(29, -8)]),
+        (VR4300, 0xE7B4FFF8, SWC1, &[Cp1Reg(20), Mem(29, -8)]),
+        (VR4300, 0xF7B4FFF8, SDC1, &[Cp1Reg(20), Mem(29, -8)]),
+        (VR4300, 0xC8830020, LWCz, &[CopReg(3), Mem(4, 0x20)]),
+        (VR4300, 0xD8830020, LDCz, &[CopReg(3), Mem(4, 0x20)]),
+        (VR4300, 0xE8830020, SWCz, &[CopReg(3), Mem(4, 0x20)]),
+        (VR4300, 0xF8830020, SDCz, &[CopReg(3), Mem(4, 0x20)]),
+        (VR4300, 0xBD150000, CACHE, &[CacheOp(0x15), Mem(8, 0)]),
+        (VR4300, 0x00000000, NOP, &[]),
+        (VR4300, 0xEC000000, Unknown, &[]),
+        (RSP, 0x40042000, MFC0, &[Reg(4), RspCp0Reg(4)]),
+        (RSP, 0x40805800, MTC0, &[Reg(0), RspCp0Reg(11)]),
+        (RSP, 0x4083D800, Unknown, &[]),
+        (RSP, 0x48020980, MFC2, &[Reg(2), VRegLane(1, 3)]),
+        (RSP, 0x48820B00, MTC2, &[Reg(2), VRegLane(1, 6)]),
+        (RSP, 0x48420800, CFC2, &[Reg(2), VCtrlReg(1)]),
+        (RSP, 0x48C21000, CTC2, &[Reg(2), VCtrlReg(2)]),
+        (RSP, 0x4A620F80, VMULF, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F81, VMULU, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F82, VRNDP, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F83, VMULQ, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F84, VMUDL, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F85, VMUDM, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F86, VMUDN, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F87, VMUDH, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F88, VMACF, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F89, VMACU, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F8A, VRNDN, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F8B, VMACQ, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F8C, VMADL, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F8D, VMADM, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F8E, VMADN, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F8F, VMADH, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F90, VADD, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A220F90, VADD, &[VReg(30), VReg(1), VRegElement(2, 1)]),
+        (RSP, 0x4A620F91, VSUB, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F93, VABS, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F94, VADDC, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620F95, VSUBC, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA0, VLT, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA1, VEQ, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA2, VNE, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA3, VGE, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA4, VCL, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA5, VCH, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA6, VCR, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA7, VMRG, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA8, VAND, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FA9, VNAND, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FAA, VOR, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FAB, VNOR, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FAC, VXOR, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4A620FAD, VNXOR, &[VReg(30), VReg(1), VRegElement(2, 3)]),
+        (RSP, 0x4BE22870, VRCP, &[VRegLane(1, 5), VRegElement(2, 15)]),
+        (RSP, 0x4BE22871, VRCPL, &[VRegLane(1, 5), VRegElement(2, 15)]),
+        (RSP, 0x4BE22872, VRCPH, &[VRegLane(1, 5), VRegElement(2, 15)]),
+        (RSP, 0x4BE22873, VMOV, &[VRegLane(1, 5), VRegElement(2, 15)]),
+        (RSP, 0x4BE22874, VRSQ, &[VRegLane(1, 5), VRegElement(2, 15)]),
+        (RSP, 0x4BE22875, VRSQL, &[VRegLane(1, 5), VRegElement(2, 15)]),
+        (RSP, 0x4BE22876, VRSQH, &[VRegLane(1, 5), VRegElement(2, 15)]),
+        (RSP, 0x4BE2E874, VRSQ, &[VRegLane(1, 29), VRegElement(2, 15)]),
+        (RSP, 0x4B20011D, VSAR, &[VReg(4), VAcc(1)]),
+        (RSP, 0x4A000037, VNOP, &[]),
+        (RSP, 0xCBA30082, LBV, &[VRegLane(3, 1), Mem(29, 2)]),
+        (RSP, 0xEBA30082, SBV, &[VRegLane(3, 1), Mem(29, 2)]),
+        (RSP, 0xCBA308FF, LSV, &[VRegLane(3, 1), Mem(29, -2)]),
+        (RSP, 0xEBA308FF, SSV, &[VRegLane(3, 1), Mem(29, -2)]),
+        (RSP, 0xCBA31082, LLV, &[VRegLane(3, 1), Mem(29, 8)]),
+        (RSP, 0xEBA31082, SLV, &[VRegLane(3, 1), Mem(29, 8)]),
+        (RSP, 0xCBA318FF, LDV, &[VRegLane(3, 1), Mem(29, -8)]),
+        (RSP, 0xEBA318FF, SDV, &[VRegLane(3, 1), Mem(29, -8)]),
+        (RSP, 0xCBA32082, LQV, &[VRegLane(3, 1), Mem(29, 32)]),
+        (RSP, 0xEBA32082, SQV, &[VRegLane(3, 1), Mem(29, 32)]),
+        (RSP, 0xCBA328FF, LRV, &[VRegLane(3, 1), Mem(29, -16)]),
+        (RSP, 0xEBA328FF, SRV, &[VRegLane(3, 1), Mem(29, -16)]),
+        (RSP, 0xCBA33082, LPV, &[VRegLane(3, 1), Mem(29, 16)]),
+        (RSP, 0xEBA33082, SPV, &[VRegLane(3, 1), Mem(29, 16)]),
+        (RSP, 0xCBA338FF, LUV, &[VRegLane(3, 1), Mem(29, -8)]),
+        (RSP, 0xEBA338FF, SUV, &[VRegLane(3, 1), Mem(29, -8)]),
+        (RSP, 0xCBA34082, LHV, &[VRegLane(3, 1), Mem(29, 32)]),
+        (RSP, 0xEBA34082, SHV, &[VRegLane(3, 1), Mem(29, 32)]),
+        (RSP, 0xCBA348FF, LFV, &[VRegLane(3, 1), Mem(29, -16)]),
+        (RSP, 0xEBA348FF, SFV, &[VRegLane(3, 1), Mem(29, -16)]),
+        (RSP, 0xCBA35082, LWV, &[VRegLane(3, 1), Mem(29, 32)]),
+        (RSP, 0xEBA35082, SWV, &[VRegLane(3, 1), Mem(29, 32)]),
+        (RSP, 0xCBA358FF, LTV, &[VRegLane(3, 1), Mem(29, -16)]),
+        (RSP, 0xEBA358FF, STV, &[VRegLane(3, 1), Mem(29, -16)]),
+    ];
+    
+    #[test]
+    fn encodings_decode_to_expected_operands() {
+        for (target, code, op, args) in ENCODINGS {
+            let instr = disassemble_target(*code, *target);
+            let decoded: Vec<Operand> = instr.args.iter().flatten().copied().collect();
+            
+            assert_eq!(instr.op, *op, "{:#010X} decoded as {}", code, instr);
+            assert_eq!(decoded, args.to_vec(), "{:#010X} decoded as {}", code, instr);
+        }
+    }
+    
+    #[test]
+    fn every_operation_has_an_encoding() {
+        for op in Operation::iter() {
+            assert!(ENCODINGS.iter().any(|(_, _, expected, _)| *expected == op), "{:?} is missing from the encoding table", op);
+        }
+    }
+    
+    #[test]
+    fn reg_names() {
+        use super::{format_args, RegNames, RegScheme::*};
+        
+        let operands = [
+            Reg(30), Reg(8), Cp0Reg(12), Cp1Reg(4), Cp1CtrlReg(31), RspCp0Reg(4), CopReg(5),
+            VReg(3), VRegElement(3, 5), VRegLane(3, 2), VCtrlReg(1), VAcc(0), Mem(29, -8),
+        ];
+        let args: Vec<Option<Operand>> = operands.iter().copied().map(Some).collect();
+        
+        for (scheme, dollar, expected) in &[
+            (Compact, false, "fp, t0, SR, f4, FCR31, SP_STATUS, $5, v3, v3[1h], v3[2], VCC, ACC_H, -0x8(sp)"),
+            (Compact, true, "$fp, $t0, $12, $f4, $31, $4, $5, $v3, $v3[1h], $v3[2], $1, $8, -0x8($sp)"),
+            (O32, false, "s8, t0, SR, f4, FCR31, SP_STATUS, $5, v3, v3[1h], v3[2], VCC, ACC_H, -0x8(sp)"),
+            (O32, true, "$s8, $t0, $12, $f4, $31, $4, $5, $v3, $v3[1h], $v3[2], $1, $8, -0x8($sp)"),
+            (N32, false, "s8, a4, SR, f4, FCR31, SP_STATUS, $5, v3, v3[1h], v3[2], VCC, ACC_H, -0x8(sp)"),
+            (N32, true, "$s8, $a4, $12, $f4, $31, $4, $5, $v3, $v3[1h], $v3[2], $1, $8, -0x8($sp)"),
+            (Numeric, false, "r30, r8, c0r12, f4, fcr31, c0r4, cr5, v3, v3[1h], v3[2], VCC, ACC_H, -0x8(r29)"),
+            (Numeric, true, "$30, $8, $12, $f4, $31, $4, $5, $v3, $v3[1h], $v3[2], $1, $8, -0x8($29)"),
+        ] {
+            assert_eq!(format_args(&args, RegNames::new(*scheme, *dollar)), *expected, "{:?} {}", scheme, dollar);
+        }
+    }
+    
+    #[test]
+    fn addresses() {
+        let rom: Vec<u8> = [0u32, 0, 0x27BDFFE8, 0xAFBF0014, 0x0C000114, 0x00000000].iter().flat_map(|word| word.to_be_bytes()).collect();
+        let disasm = Disassembly::from_rom(&rom, 8, 16, 0x80000400, VR4300);
+        
+        assert_eq!(disasm.len(), 4);
+        assert_eq!(disasm.index_of(0x80000408), Some(2));
+        assert_eq!(disasm.index_of(0x80000402), None);
+        assert_eq!(disasm.index_of(0x80000410), None);
+        assert_eq!(disasm.index_of(0x800003FC), None);
+        assert_eq!(disasm.get(0x80000408).map(|instr| instr.op), Some(JAL));
+        assert_eq!(disasm.iter().map(|(addr, _)| addr).collect::<Vec<u32>>(), vec![0x80000400, 0x80000404, 0x80000408, 0x8000040C]);
+        
+        assert_eq!(disasm.rom_to_vaddr(8), Some(0x80000400));
+        assert_eq!(disasm.rom_to_vaddr(0x14), Some(0x8000040C));
+        assert_eq!(disasm.rom_to_vaddr(0x18), None);
+        assert_eq!(disasm.rom_to_vaddr(4), None);
+        
+        // Ranges past the end are clamped, down to whole words
+        assert_eq!(Disassembly::from_rom(&rom, 16, 0x100, 0x80000400, VR4300).raw, vec![0x0C000114, 0x00000000]);
+        assert_eq!(Disassembly::from_rom(&rom[..22], 16, 8, 0x80000400, VR4300).raw, vec![0x0C000114]);
+        assert_eq!(Disassembly::from_rom(&rom, 0x100, 8, 0x80000400, VR4300).len(), 0);
+        assert_eq!(Disassembly::from_rom(&rom, 8, usize::MAX, 0x80000400, VR4300).len(), 4);
+        assert_eq!(disasm.vaddr_to_rom(0x80000404), Some(0xC));
+        assert_eq!(disasm.vaddr_to_rom(0x80000410), None);
+        
+        let slice = disasm.slice(0x80000404..0x80000410).unwrap();
+        assert_eq!(slice.len(), 3);
+        assert_eq!(slice.vaddr, Some(0x80000404));
+        assert_eq!(slice.rom_offset, Some(0xC));
+        assert!(slice.get(0x80000408) == disasm.get(0x80000408));
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = disasm.slice(0x80000408..0x80000404);
+        assert!(reversed.is_none());
+        assert!(disasm.slice(0x80000400..0x80000414).is_none());
+        assert!(disasm.slice(0x80000410..0x80000410).unwrap().is_empty());
+        
+        // ROM offsets past u32::MAX don't exist
+        let end = Disassembly::from_u32(&[0, 0]).at_vaddr(0x80000400).with_rom_offset(u32::MAX - 3);
+        assert_eq!(end.vaddr_to_rom(0x80000400), Some(u32::MAX - 3));
+        assert_eq!(end.vaddr_to_rom(0x80000404), None);
+        assert_eq!(end.rom_to_vaddr(u32::MAX - 3), Some(0x80000400));
+        assert_eq!(end.slice(0x80000400..0x80000404).unwrap().rom_offset, Some(u32::MAX - 3));
+        assert!(end.slice(0x80000404..0x80000408).is_none());
+    }
+}
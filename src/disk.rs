@@ -0,0 +1,418 @@
+use crate::cic::{CicFamily, CicTable};
+use crate::disassembly::{Disassembly, Target};
+use crate::rom::{to_u16, to_u32, Rom};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// Size of an `.ndd` dump, which holds every LBA in order with no gaps.
+pub const NDD_SIZE: usize = 0x3DEC800;
+/// Number of LBAs on a disk, system area included.
+pub const LBA_COUNT: u16 = 4316;
+/// LBAs reserved for the system area and disk ID. Games address LBAs relative to the end of it.
+pub const SYSTEM_LBAS: u16 = 24;
+/// A block, which is what an LBA addresses, is half a track.
+pub const SECTORS_PER_BLOCK: usize = 85;
+/// Where the 64DD IPL ROM is mapped, in PI domain 1.
+pub const IPL_ROM_ADDRESS: u32 = 0xA6000000;
+
+/// LBAs holding the system area, in the order the IPL tries them. Retail disks use the first 4, development disks
+/// the rest.
+const SYSTEM_AREA_LBAS: [u16; 8] = [0, 1, 8, 9, 2, 3, 10, 11];
+/// LBAs holding the disk ID, primary then backup.
+const DISK_ID_LBAS: [u16; 2] = [14, 15];
+/// Sector size of each physical zone. Zones 0-7 are on head 0, and 8-15 on head 1.
+const ZONE_SECTOR_SIZE: [usize; 16] = [232, 216, 208, 192, 176, 160, 144, 128, 216, 208, 192, 176, 160, 144, 128, 112];
+/// Usable tracks in each physical zone, after the 12 that every zone sets aside for defects.
+const ZONE_TRACKS: [u16; 16] = [146, 146, 137, 137, 137, 137, 137, 102, 146, 146, 137, 137, 137, 137, 137, 102];
+/// The physical zone each logical zone maps to, for each disk type. Types with more of the disk on head 0 have a
+/// bigger ROM area.
+const DISK_TYPE_ZONES: [[u8; 16]; 7] = [
+    [0, 1, 2, 9, 8, 3, 4, 5, 6, 7, 15, 14, 13, 12, 11, 10],
+    [0, 1, 2, 3, 10, 9, 8, 4, 5, 6, 7, 15, 14, 13, 12, 11],
+    [0, 1, 2, 3, 4, 11, 10, 9, 8, 5, 6, 7, 15, 14, 13, 12],
+    [0, 1, 2, 3, 4, 5, 12, 11, 10, 9, 8, 6, 7, 15, 14, 13],
+    [0, 1, 2, 3, 4, 5, 6, 13, 12, 11, 10, 9, 8, 7, 15, 14],
+    [0, 1, 2, 3, 4, 5, 6, 7, 14, 13, 12, 11, 10, 9, 8, 15],
+    [0, 1, 2, 3, 4, 5, 6, 7, 15, 14, 13, 12, 11, 10, 9, 8],
+];
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum DiskRegion {
+    Japan,
+    NorthAmerica,
+    Development,
+    Unknown(u32),
+}
+
+impl From<u32> for DiskRegion {
+    fn from(code: u32) -> Self {
+        match code {
+            0xE848D316 => DiskRegion::Japan,
+            0x2263EE56 => DiskRegion::NorthAmerica,
+            0x00000000 => DiskRegion::Development,
+            _ => DiskRegion::Unknown(code),
+        }
+    }
+}
+
+/// The system area, which tells the drive how the disk is laid out and the IPL what to boot.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct SystemArea {
+    pub region: DiskRegion,
+    /// Always `0x10` on disks the 64DD can read.
+    pub format: u8,
+    /// 0 to 6, see [`DISK_TYPE_ZONES`].
+    pub disk_type: u8,
+    /// Number of blocks the IPL loads, starting at the first LBA after the system area.
+    pub ipl_load_blocks: u16,
+    /// Where in RDRAM the IPL loads those blocks and jumps to.
+    pub ipl_load_address: u32,
+    /// Last LBA of the read-only area, relative to the end of the system area.
+    pub rom_end_lba: u16,
+    /// First LBA of the writable area, relative to the end of the system area.
+    pub ram_start_lba: u16,
+    /// Last LBA of the writable area, relative to the end of the system area.
+    pub ram_end_lba: u16,
+}
+
+impl SystemArea {
+    /// Parses the start of a system area block. `None` if it isn't a valid system area, e.g. a blank LBA.
+    pub fn parse(bytes: &[u8]) -> Option<SystemArea> {
+        let bytes = bytes.get(0..0xE8)?;
+        let format = bytes[4];
+        let disk_type = bytes[5] & 0x0F;
+        
+        if format != 0x10 || bytes[5] >> 4 != 1 || disk_type as usize >= DISK_TYPE_ZONES.len() {
+            return None;
+        }
+        
+        Some(SystemArea {
+            region: DiskRegion::from(to_u32(&bytes[0x00..0x04])),
+            format,
+            disk_type,
+            ipl_load_blocks: to_u16(&bytes[0x06..0x08]),
+            ipl_load_address: to_u32(&bytes[0x1C..0x20]),
+            rom_end_lba: to_u16(&bytes[0xE0..0xE2]),
+            ram_start_lba: to_u16(&bytes[0xE2..0xE4]),
+            ram_end_lba: to_u16(&bytes[0xE4..0xE6]),
+        })
+    }
+}
+
+/// The disk ID, which identifies the game much like a cartridge header.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct DiskId {
+    /// Same format as a cartridge's, e.g. `DMPJ`.
+    pub game_code: [u8; 4],
+    pub version: u8,
+    /// Which disk of a multi-disk game this is.
+    pub disk_number: u8,
+    pub ram_use: u8,
+    pub disk_use: u8,
+    /// Factory line number and production time.
+    pub serial: [u8; 16],
+    /// 2 character publisher code, e.g. `01` for Nintendo.
+    pub company: [u8; 2],
+}
+
+impl DiskId {
+    pub fn parse(bytes: &[u8]) -> Option<DiskId> {
+        let bytes = bytes.get(0..0x20)?;
+        let mut serial = [0u8; 16];
+        serial.copy_from_slice(&bytes[0x08..0x18]);
+        
+        Some(DiskId {
+            game_code: [bytes[0], bytes[1], bytes[2], bytes[3]],
+            version: bytes[4],
+            disk_number: bytes[5],
+            ram_use: bytes[6],
+            disk_use: bytes[7],
+            serial,
+            company: [bytes[0x18], bytes[0x19]],
+        })
+    }
+}
+
+/// A run of LBAs in one physical zone, all with the same block size.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Zone {
+    /// Physical zone, 0-7 on head 0 and 8-15 on head 1.
+    pub pzone: u8,
+    pub start_lba: u16,
+    pub lbas: u16,
+    pub block_size: usize,
+    /// Offset of the first block within an `.ndd` dump.
+    pub offset: usize,
+}
+
+impl Zone {
+    /// The zones of a disk type, in LBA order. `None` if there's no such type.
+    pub fn layout(disk_type: u8) -> Option<Vec<Zone>> {
+        let mut zones = Vec::new();
+        let mut start_lba = 0;
+        let mut offset = 0;
+        
+        for &pzone in DISK_TYPE_ZONES.get(disk_type as usize)? {
+            let zone = Zone {
+                pzone,
+                start_lba,
+                lbas: ZONE_TRACKS[pzone as usize] * 2,
+                block_size: ZONE_SECTOR_SIZE[pzone as usize] * SECTORS_PER_BLOCK,
+                offset,
+            };
+            start_lba += zone.lbas;
+            offset += zone.lbas as usize * zone.block_size;
+            zones.push(zone);
+        }
+        
+        Some(zones)
+    }
+    
+    pub fn contains(&self, lba: u16) -> bool {
+        lba >= self.start_lba && lba - self.start_lba < self.lbas
+    }
+    
+    pub fn head(&self) -> u8 {
+        self.pzone / 8
+    }
+}
+
+#[derive(Debug)]
+pub enum DiskError {
+    Io(std::io::Error),
+    /// Not the size of an `.ndd` dump.
+    WrongSize(usize),
+    /// None of the system area LBAs hold a valid system area.
+    NoSystemArea,
+    /// Asked to disassemble an IPL ROM whose bootcode isn't a CIC-8303 one.
+    NotIplRom(CicFamily),
+}
+
+impl Display for DiskError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use DiskError::*;
+        
+        match self {
+            Io(err) => write!(f, "{}", err),
+            WrongSize(len) => write!(f, "{:#X} bytes is the wrong size for a disk image, expected {:#X}", len, NDD_SIZE),
+            NoSystemArea => write!(f, "no valid system area found"),
+            NotIplRom(cic) => write!(f, "bootcode is CIC {}, not the 64DD IPL's 8303", cic),
+        }
+    }
+}
+
+impl std::error::Error for DiskError {}
+
+impl From<std::io::Error> for DiskError {
+    fn from(err: std::io::Error) -> Self {
+        DiskError::Io(err)
+    }
+}
+
+/// A 64DD disk image in `.ndd` format, along with its parsed system area and disk ID.
+#[derive(Debug)]
+pub struct DiskImage {
+    system_area: SystemArea,
+    disk_id: DiskId,
+    zones: Vec<Zone>,
+    data: Vec<u8>,
+}
+
+impl DiskImage {
+    pub fn new(data: Vec<u8>) -> Result<DiskImage, DiskError> {
+        if data.len() != NDD_SIZE {
+            return Err(DiskError::WrongSize(data.len()));
+        }
+        
+        // The system area is always in zone 0, which is the first zone of every disk type.
+        let block_size = ZONE_SECTOR_SIZE[0] * SECTORS_PER_BLOCK;
+        let block = |lba: u16| &data[(lba as usize * block_size)..((lba as usize + 1) * block_size)];
+        
+        let system_area = SYSTEM_AREA_LBAS.iter().find_map(|&lba| SystemArea::parse(block(lba))).ok_or(DiskError::NoSystemArea)?;
+        let disk_id = DISK_ID_LBAS.iter().filter_map(|&lba| DiskId::parse(block(lba))).find(|id| id.game_code != [0; 4])
+            .or_else(|| DiskId::parse(block(DISK_ID_LBAS[0])))
+            .ok_or(DiskError::NoSystemArea)?;
+        let zones = Zone::layout(system_area.disk_type).ok_or(DiskError::NoSystemArea)?;
+        
+        Ok(DiskImage { system_area, disk_id, zones, data })
+    }
+    
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<DiskImage, DiskError> {
+        DiskImage::new(std::fs::read(path)?)
+    }
+    
+    pub fn system_area(&self) -> &SystemArea {
+        &self.system_area
+    }
+    
+    pub fn disk_id(&self) -> &DiskId {
+        &self.disk_id
+    }
+    
+    pub fn disk_type(&self) -> u8 {
+        self.system_area.disk_type
+    }
+    
+    /// The zones of this disk's type, in LBA order.
+    pub fn zones(&self) -> &[Zone] {
+        &self.zones
+    }
+    
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    
+    pub fn zone_of(&self, lba: u16) -> Option<&Zone> {
+        self.zones.iter().find(|zone| zone.contains(lba))
+    }
+    
+    /// Offset of an LBA within the image. LBAs count from the start of the disk, system area included.
+    pub fn lba_offset(&self, lba: u16) -> Option<usize> {
+        let zone = self.zone_of(lba)?;
+        
+        Some(zone.offset + (lba - zone.start_lba) as usize * zone.block_size)
+    }
+    
+    /// The block at an LBA.
+    pub fn block(&self, lba: u16) -> Option<&[u8]> {
+        let zone = self.zone_of(lba)?;
+        let offset = self.lba_offset(lba)?;
+        
+        Some(&self.data[offset..(offset + zone.block_size)])
+    }
+    
+    /// Offset and size of the blocks the IPL loads. Blocks are contiguous in an `.ndd`, even across zones.
+    pub fn ipl_load_region(&self) -> (usize, usize) {
+        let start = self.lba_offset(SYSTEM_LBAS).unwrap_or(NDD_SIZE);
+        let end = SYSTEM_LBAS.checked_add(self.system_area.ipl_load_blocks)
+            .filter(|end| *end < LBA_COUNT)
+            .and_then(|end| self.lba_offset(end))
+            .unwrap_or(NDD_SIZE);
+        
+        (start, end - start)
+    }
+    
+    /// Disassembles the blocks the IPL loads, at the address it loads them to.
+    pub fn ipl_disassembly(&self) -> Disassembly {
+        let (offset, len) = self.ipl_load_region();
+        
+        Disassembly::from_rom(&self.data, offset, len, self.system_area.ipl_load_address, Target::VR4300)
+    }
+}
+
+impl Rom {
+    /// Disassembles a 64DD IPL ROM where it's mapped, checking its bootcode is the CIC-8303 one. The IPL ROM's header
+    /// starts with 0x80270740 rather than the cartridge magic, so it has to be loaded with [`Rom::lenient`].
+    pub fn ipl_rom_disassembly(&self) -> Result<Disassembly, DiskError> {
+        self.ipl_rom_disassembly_with(&CicTable::builtin())
+    }
+    
+    pub fn ipl_rom_disassembly_with(&self, table: &CicTable) -> Result<Disassembly, DiskError> {
+        match self.cic_with(table) {
+            CicFamily::Cic8303 => Ok(Disassembly::from_rom(self.data(), 0, self.data().len(), IPL_ROM_ADDRESS, Target::VR4300)),
+            cic => Err(DiskError::NotIplRom(cic)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiskError, DiskImage, DiskRegion, Zone, LBA_COUNT, NDD_SIZE, SYSTEM_LBAS};
+    use crate::cic::{CicFamily, CicTable};
+    use crate::disassembly::Operation;
+    use crate::rom::{test_rom, Rom};
+    
+    const BLOCK: usize = 232 * 85;
+    
+    fn system_area(disk_type: u8) -> Vec<u8> {
+        let mut bytes = vec![0u8; 0xE8];
+        bytes[0..4].copy_from_slice(&0x2263EE56u32.to_be_bytes());
+        bytes[4] = 0x10;
+        bytes[5] = 0x10 | disk_type;
+        bytes[6..8].copy_from_slice(&2u16.to_be_bytes());
+        bytes[0x1C..0x20].copy_from_slice(&0x80000400u32.to_be_bytes());
+        bytes[0xE0..0xE2].copy_from_slice(&0x05A1u16.to_be_bytes());
+        bytes
+    }
+    
+    fn image(system_lba: usize) -> Vec<u8> {
+        let mut data = vec![0u8; NDD_SIZE];
+        data[(system_lba * BLOCK)..(system_lba * BLOCK + 0xE8)].copy_from_slice(&system_area(1));
+        data[(15 * BLOCK)..(15 * BLOCK + 8)].copy_from_slice(b"DMPJ\x00\x01\x00\x00");
+        data[(24 * BLOCK)..(24 * BLOCK + 4)].copy_from_slice(&0x3C1D8040u32.to_be_bytes());
+        data
+    }
+    
+    #[test]
+    fn layout() {
+        for disk_type in 0..7 {
+            let zones = Zone::layout(disk_type).unwrap();
+            let last = zones.last().unwrap();
+            
+            assert_eq!(last.start_lba + last.lbas, LBA_COUNT);
+            assert_eq!(last.offset + last.lbas as usize * last.block_size, NDD_SIZE);
+        }
+        assert!(Zone::layout(7).is_none());
+        
+        // libleo's LBA table for type 0
+        let ends: Vec<u16> = Zone::layout(0).unwrap().iter().map(|zone| zone.start_lba + zone.lbas).collect();
+        assert_eq!(ends, vec![0x124, 0x248, 0x35A, 0x47E, 0x5A2, 0x6B4, 0x7C6, 0x8D8, 0x9EA, 0xAB6, 0xB82, 0xC94, 0xDA6, 0xEB8, 0xFCA, 0x10DC]);
+    }
+    
+    #[test]
+    fn disk_image() {
+        let disk = DiskImage::new(image(0)).unwrap();
+        let system = disk.system_area();
+        
+        assert_eq!(system.region, DiskRegion::NorthAmerica);
+        assert_eq!(disk.disk_type(), 1);
+        assert_eq!(system.ipl_load_blocks, 2);
+        assert_eq!(system.rom_end_lba, 0x05A1);
+        assert_eq!(&disk.disk_id().game_code, b"DMPJ");
+        assert_eq!(disk.disk_id().disk_number, 1);
+        
+        assert_eq!(disk.lba_offset(1), Some(BLOCK));
+        assert_eq!(disk.lba_offset(0x124), Some(0x124 * BLOCK));
+        assert_eq!(disk.zone_of(0x248).map(|zone| (zone.pzone, zone.head(), zone.block_size)), Some((2, 0, 208 * 85)));
+        assert_eq!(disk.block(LBA_COUNT - 1).map(|block| block.len()), Some(176 * 85));
+        assert_eq!(disk.lba_offset(LBA_COUNT), None);
+        
+        assert_eq!(disk.ipl_load_region(), (SYSTEM_LBAS as usize * BLOCK, 2 * BLOCK));
+        let ipl = disk.ipl_disassembly();
+        assert_eq!(ipl.vaddr, Some(0x80000400));
+        assert_eq!(ipl.rom_offset, Some(SYSTEM_LBAS as u32 * BLOCK as u32));
+        assert_eq!(ipl.len(), 2 * BLOCK / 4);
+        assert_eq!(ipl.get(0x80000400).map(|instr| instr.op), Some(Operation::LUI));
+        
+        // Falls back to the backup copies
+        assert_eq!(DiskImage::new(image(9)).unwrap().disk_type(), 1);
+    }
+    
+    #[test]
+    fn errors() {
+        assert!(matches!(DiskImage::new(vec![0u8; 0x1000]), Err(DiskError::WrongSize(0x1000))));
+        assert!(matches!(DiskImage::new(vec![0u8; NDD_SIZE]), Err(DiskError::NoSystemArea)));
+        
+        let mut data = image(0);
+        data[5] = 0x17;
+        assert!(matches!(DiskImage::new(data), Err(DiskError::NoSystemArea)));
+    }
+    
+    #[test]
+    fn ipl_rom() {
+        let rom = test_rom(0x2000).with_code(0x40, &[0x40086800]).rom();
+        
+        assert!(matches!(rom.ipl_rom_disassembly(), Err(DiskError::NotIplRom(CicFamily::Unknown))));
+        
+        let table = CicTable::parse(&format!("{:08X} 8303 test", rom.bootcode_hash())).unwrap();
+        let disasm = rom.ipl_rom_disassembly_with(&table).unwrap();
+        assert_eq!(disasm.vaddr, Some(0xA6000000));
+        assert_eq!(disasm.len(), 0x800);
+        assert_eq!(disasm.get(0xA6000040).map(|instr| instr.op), Some(Operation::MFC0));
+        
+        // Real IPL ROMs start with 0x80270740 instead of the cartridge magic
+        let (ipl, _) = Rom::lenient(test_rom(0x2000).with_code(0, &[0x80270740]).with_code(0x40, &[0x40086800]).into_bytes());
+        let table = CicTable::parse(&format!("{:08X} 8303 test", ipl.bootcode_hash())).unwrap();
+        assert_eq!(ipl.ipl_rom_disassembly_with(&table).unwrap().raw[0], 0x80270740);
+    }
+}
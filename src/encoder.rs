@@ -0,0 +1,638 @@
+use crate::disassembly::{Instruction, Operand, Operation, Target};
+use crate::rsp::is_scalar_op;
+use std::fmt::{Display, Formatter};
+
+/// Why an [`Instruction`] can't be encoded.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum EncodeError {
+    /// The target has no encoding for the operation. This includes [`Operation::Unknown`].
+    Unsupported(Operation, Target),
+    /// The coprocessor number in bits 27..26 of `code` can't be used with the generic operation (e.g. `LWC0` is `LL`).
+    Coprocessor(Operation, u8),
+    /// The number of operands given, and the range the operation accepts.
+    OperandCount(usize, usize, usize),
+    /// The operand at the index isn't the kind the operation has in that position.
+    WrongOperand(usize, Operand),
+    /// The operand at the index doesn't fit its field.
+    OutOfRange(usize, Operand),
+    /// The operand at the index is an absolute address, which can only be encoded with [`Instruction::encode_at`].
+    Unresolved(usize),
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use EncodeError::*;
+        
+        match self {
+            Unsupported(op, target) => write!(f, "{} can't be encoded for the {:?}", op, target),
+            Coprocessor(op, z) => write!(f, "{} can't be used with coprocessor {}", op, z),
+            OperandCount(found, min, max) if min == max => write!(f, "expected {} operands, found {}", min, found),
+            OperandCount(found, min, max) => write!(f, "expected {} to {} operands, found {}", min, max, found),
+            WrongOperand(index, operand) => write!(f, "operand {} ({}) is the wrong kind", index, operand),
+            OutOfRange(index, operand) => write!(f, "operand {} ({}) is out of range", index, operand),
+            Unresolved(index) => write!(f, "operand {} is an absolute address and needs the instruction address", index),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Instruction fields holding a register number, by the bit they start at.
+#[derive(Copy, Clone)]
+pub(crate) enum Field {
+    Rs = 21,
+    Rt = 16,
+    Rd = 11,
+    Sa = 6,
+}
+
+/// How an operand is parsed and where it is placed in the instruction word.
+#[derive(Copy, Clone)]
+pub(crate) enum Slot {
+    Gpr(Field),
+    Cp0,
+    RspCp0,
+    Fpr(Field),
+    Fcr,
+    Cop(Field),
+    VReg(Field),
+    /// `vt` with the element specifier in bits 24..21.
+    VElement,
+    /// `vd` with the destination element in the `vs` field (VRCP, VMOV, etc).
+    VLaneDest,
+    /// `rd` with the element in bits 10..7 (MFC2/MTC2).
+    VLaneMove,
+    /// `vt` with the element in bits 10..7 (vector loads and stores).
+    VLaneMem,
+    VCtrl,
+    VAcc,
+    Shift,
+    Imm16,
+    Lit16,
+    Branch,
+    Jump,
+    Mem,
+    /// Vector load/store address, with the offset scaled down by the given shift into 7 bits.
+    VMem(u32),
+    CacheOp,
+    /// The optional code of SYSCALL.
+    Code20,
+    /// The optional first code of BREAK, in bits 25..16. A second code goes in the [`Slot::Code10`] bits below it.
+    BreakCode,
+    /// The optional code of the register-register traps.
+    Code10,
+    /// The optional SYNC type.
+    Stype,
+    Cofun,
+}
+
+impl Slot {
+    pub(crate) fn optional(&self) -> bool {
+        matches!(self, Slot::Code20 | Slot::BreakCode | Slot::Code10 | Slot::Stype)
+    }
+}
+
+const RD_RS_RT: &[Slot] = &[Slot::Gpr(Field::Rd), Slot::Gpr(Field::Rs), Slot::Gpr(Field::Rt)];
+const RD_RT_RS: &[Slot] = &[Slot::Gpr(Field::Rd), Slot::Gpr(Field::Rt), Slot::Gpr(Field::Rs)];
+const RD_RT_SA: &[Slot] = &[Slot::Gpr(Field::Rd), Slot::Gpr(Field::Rt), Slot::Shift];
+const RT_RS_IMM: &[Slot] = &[Slot::Gpr(Field::Rt), Slot::Gpr(Field::Rs), Slot::Imm16];
+const RT_RS_LIT: &[Slot] = &[Slot::Gpr(Field::Rt), Slot::Gpr(Field::Rs), Slot::Lit16];
+const RS_RT: &[Slot] = &[Slot::Gpr(Field::Rs), Slot::Gpr(Field::Rt)];
+const RS_RT_CODE: &[Slot] = &[Slot::Gpr(Field::Rs), Slot::Gpr(Field::Rt), Slot::Code10];
+const RS_RT_BRANCH: &[Slot] = &[Slot::Gpr(Field::Rs), Slot::Gpr(Field::Rt), Slot::Branch];
+const RS_BRANCH: &[Slot] = &[Slot::Gpr(Field::Rs), Slot::Branch];
+const RS_IMM: &[Slot] = &[Slot::Gpr(Field::Rs), Slot::Imm16];
+const RT_MEM: &[Slot] = &[Slot::Gpr(Field::Rt), Slot::Mem];
+const FT_MEM: &[Slot] = &[Slot::Fpr(Field::Rt), Slot::Mem];
+const COP_MEM: &[Slot] = &[Slot::Cop(Field::Rt), Slot::Mem];
+const RT_FS: &[Slot] = &[Slot::Gpr(Field::Rt), Slot::Fpr(Field::Rd)];
+const FPU3: &[Slot] = &[Slot::Fpr(Field::Sa), Slot::Fpr(Field::Rd), Slot::Fpr(Field::Rt)];
+const FPU2: &[Slot] = &[Slot::Fpr(Field::Sa), Slot::Fpr(Field::Rd)];
+const FPU_CMP: &[Slot] = &[Slot::Fpr(Field::Rd), Slot::Fpr(Field::Rt)];
+const VU3: &[Slot] = &[Slot::VReg(Field::Sa), Slot::VReg(Field::Rd), Slot::VElement];
+const VU_LANE: &[Slot] = &[Slot::VLaneDest, Slot::VElement];
+
+const fn special(funct: u32) -> u32 { funct }
+const fn regimm(rt: u32) -> u32 { (0x01 << 26) | (rt << 16) }
+const fn opcode(op: u32) -> u32 { op << 26 }
+const fn cop(z: u32, rs: u32) -> u32 { ((0x10 | z) << 26) | (rs << 21) }
+const fn fpu(fmt: u32, funct: u32) -> u32 { cop(1, fmt) | funct }
+const fn vu(funct: u32) -> u32 { cop(2, 0x10) | funct }
+const fn vmem(op: u32, funct: u32) -> u32 { (op << 26) | (funct << 11) }
+
+/// Fixed bits and operand slots of the VR4300 operations. Generic coprocessor operations are given for COP0, with
+/// the coprocessor number added in from the instruction's `code`.
+fn cpu_layout(op: Operation) -> Option<(u32, &'static [Slot])> {
+    use self::{Field::*, Operation::*, Slot::*};
+    
+    Some(match op {
+        NOP => (0, &[]),
+        
+        SLL => (special(0x00), RD_RT_SA),
+        SRL => (special(0x02), RD_RT_SA),
+        SRA => (special(0x03), RD_RT_SA),
+        SLLV => (special(0x04), RD_RT_RS),
+        SRLV => (special(0x06), RD_RT_RS),
+        SRAV => (special(0x07), RD_RT_RS),
+        JR => (special(0x08), &[Gpr(Rs)]),
+        JALR => (special(0x09), &[Gpr(Rd), Gpr(Rs)]),
+        SYSCALL => (special(0x0C), &[Code20]),
+        BREAK => (special(0x0D), &[BreakCode, Code10]),
+        SYNC => (special(0x0F), &[Stype]),
+        
+        MFHI => (special(0x10), &[Gpr(Rd)]),
+        MTHI => (special(0x11), &[Gpr(Rs)]),
+        MFLO => (special(0x12), &[Gpr(Rd)]),
+        MTLO => (special(0x13), &[Gpr(Rs)]),
+        DSLLV => (special(0x14), RD_RT_RS),
+        DSRLV => (special(0x16), RD_RT_RS),
+        DSRAV => (special(0x17), RD_RT_RS),
+        MULT => (special(0x18), RS_RT),
+        MULTU => (special(0x19), RS_RT),
+        DIV => (special(0x1A), RS_RT),
+        DIVU => (special(0x1B), RS_RT),
+        DMULT => (special(0x1C), RS_RT),
+        DMULTU => (special(0x1D), RS_RT),
+        DDIV => (special(0x1E), RS_RT),
+        DDIVU => (special(0x1F), RS_RT),
+        
+        ADD => (special(0x20), RD_RS_RT),
+        ADDU => (special(0x21), RD_RS_RT),
+        SUB => (special(0x22), RD_RS_RT),
+        SUBU => (special(0x23), RD_RS_RT),
+        AND => (special(0x24), RD_RS_RT),
+        OR => (special(0x25), RD_RS_RT),
+        XOR => (special(0x26), RD_RS_RT),
+        NOR => (special(0x27), RD_RS_RT),
+        SLT => (special(0x2A), RD_RS_RT),
+        SLTU => (special(0x2B), RD_RS_RT),
+        DADD => (special(0x2C), RD_RS_RT),
+        DADDU => (special(0x2D), RD_RS_RT),
+        DSUB => (special(0x2E), RD_RS_RT),
+        DSUBU => (special(0x2F), RD_RS_RT),
+        
+        TGE => (special(0x30), RS_RT_CODE),
+        TGEU => (special(0x31), RS_RT_CODE),
+        TLT => (special(0x32), RS_RT_CODE),
+        TLTU => (special(0x33), RS_RT_CODE),
+        TEQ => (special(0x34), RS_RT_CODE),
+        TNE => (special(0x36), RS_RT_CODE),
+        
+        DSLL => (special(0x38), RD_RT_SA),
+        DSRL => (special(0x3A), RD_RT_SA),
+        DSRA => (special(0x3B), RD_RT_SA),
+        DSLL32 => (special(0x3C), RD_RT_SA),
+        DSRL32 => (special(0x3E), RD_RT_SA),
+        DSRA32 => (special(0x3F), RD_RT_SA),
+        
+        BLTZ => (regimm(0x00), RS_BRANCH),
+        BGEZ => (regimm(0x01), RS_BRANCH),
+        BLTZL => (regimm(0x02), RS_BRANCH),
+        BGEZL => (regimm(0x03), RS_BRANCH),
+        TGEI => (regimm(0x08), RS_IMM),
+        TGEIU => (regimm(0x09), RS_IMM),
+        TLTI => (regimm(0x0A), RS_IMM),
+        TLTIU => (regimm(0x0B), RS_IMM),
+        TEQI => (regimm(0x0C), RS_IMM),
+        TNEI => (regimm(0x0E), RS_IMM),
+        BLTZAL => (regimm(0x10), RS_BRANCH),
+        BGEZAL => (regimm(0x11), RS_BRANCH),
+        BLTZALL => (regimm(0x12), RS_BRANCH),
+        BGEZALL => (regimm(0x13), RS_BRANCH),
+        
+        J => (opcode(0x02), &[Jump]),
+        JAL => (opcode(0x03), &[Jump]),
+        BEQ => (opcode(0x04), RS_RT_BRANCH),
+        BNE => (opcode(0x05), RS_RT_BRANCH),
+        BLEZ => (opcode(0x06), RS_BRANCH),
+        BGTZ => (opcode(0x07), RS_BRANCH),
+        ADDI => (opcode(0x08), RT_RS_IMM),
+        ADDIU => (opcode(0x09), RT_RS_IMM),
+        SLTI => (opcode(0x0A), RT_RS_IMM),
+        SLTIU => (opcode(0x0B), RT_RS_IMM),
+        ANDI => (opcode(0x0C), RT_RS_LIT),
+        ORI => (opcode(0x0D), RT_RS_LIT),
+        XORI => (opcode(0x0E), RT_RS_LIT),
+        LUI => (opcode(0x0F), &[Gpr(Rt), Lit16]),
+        
+        MFC0 => (cop(0, 0x00), &[Gpr(Rt), Cp0]),
+        DMFC0 => (cop(0, 0x01), &[Gpr(Rt), Cp0]),
+        MTC0 => (cop(0, 0x04), &[Gpr(Rt), Cp0]),
+        DMTC0 => (cop(0, 0x05), &[Gpr(Rt), Cp0]),
+        TLBR => (cop(0, 0x10) | 0x01, &[]),
+        TLBWI => (cop(0, 0x10) | 0x02, &[]),
+        TLBWR => (cop(0, 0x10) | 0x06, &[]),
+        TLBP => (cop(0, 0x10) | 0x08, &[]),
+        ERET => (cop(0, 0x10) | 0x18, &[]),
+        
+        MFCz => (cop(0, 0x00), &[Gpr(Rt), Cop(Rd)]),
+        CFCz => (cop(0, 0x02), &[Gpr(Rt), Cop(Rd)]),
+        MTCz => (cop(0, 0x04), &[Gpr(Rt), Cop(Rd)]),
+        CTCz => (cop(0, 0x06), &[Gpr(Rt), Cop(Rd)]),
+        BCzF => (cop(0, 0x08), &[Branch]),
+        BCzT => (cop(0, 0x08) | (0x01 << 16), &[Branch]),
+        BCzFL => (cop(0, 0x08) | (0x02 << 16), &[Branch]),
+        BCzTL => (cop(0, 0x08) | (0x03 << 16), &[Branch]),
+        COPz => (cop(0, 0x10), &[Cofun]),
+        
+        MFC1 => (cop(1, 0x00), RT_FS),
+        DMFC1 => (cop(1, 0x01), RT_FS),
+        CFC1 => (cop(1, 0x02), &[Gpr(Rt), Fcr]),
+        MTC1 => (cop(1, 0x04), RT_FS),
+        DMTC1 => (cop(1, 0x05), RT_FS),
+        CTC1 => (cop(1, 0x06), &[Gpr(Rt), Fcr]),
+        BC1F => (cop(1, 0x08), &[Branch]),
+        BC1T => (cop(1, 0x08) | (0x01 << 16), &[Branch]),
+        BC1FL => (cop(1, 0x08) | (0x02 << 16), &[Branch]),
+        BC1TL => (cop(1, 0x08) | (0x03 << 16), &[Branch]),
+        
+        ADD_S => (fpu(0x10, 0x00), FPU3),
+        ADD_D => (fpu(0x11, 0x00), FPU3),
+        SUB_S => (fpu(0x10, 0x01), FPU3),
+        SUB_D => (fpu(0x11, 0x01), FPU3),
+        MUL_S => (fpu(0x10, 0x02), FPU3),
+        MUL_D => (fpu(0x11, 0x02), FPU3),
+        DIV_S => (fpu(0x10, 0x03), FPU3),
+        DIV_D => (fpu(0x11, 0x03), FPU3),
+        SQRT_S => (fpu(0x10, 0x04), FPU2),
+        SQRT_D => (fpu(0x11, 0x04), FPU2),
+        ABS_S => (fpu(0x10, 0x05), FPU2),
+        ABS_D => (fpu(0x11, 0x05), FPU2),
+        MOV_S => (fpu(0x10, 0x06), FPU2),
+        MOV_D => (fpu(0x11, 0x06), FPU2),
+        NEG_S => (fpu(0x10, 0x07), FPU2),
+        NEG_D => (fpu(0x11, 0x07), FPU2),
+        ROUND_L_S => (fpu(0x10, 0x08), FPU2),
+        ROUND_L_D => (fpu(0x11, 0x08), FPU2),
+        TRUNC_L_S => (fpu(0x10, 0x09), FPU2),
+        TRUNC_L_D => (fpu(0x11, 0x09), FPU2),
+        CEIL_L_S => (fpu(0x10, 0x0A), FPU2),
+        CEIL_L_D => (fpu(0x11, 0x0A), FPU2),
+        FLOOR_L_S => (fpu(0x10, 0x0B), FPU2),
+        FLOOR_L_D => (fpu(0x11, 0x0B), FPU2),
+        ROUND_W_S => (fpu(0x10, 0x0C), FPU2),
+        ROUND_W_D => (fpu(0x11, 0x0C), FPU2),
+        TRUNC_W_S => (fpu(0x10, 0x0D), FPU2),
+        TRUNC_W_D => (fpu(0x11, 0x0D), FPU2),
+        CEIL_W_S => (fpu(0x10, 0x0E), FPU2),
+        CEIL_W_D => (fpu(0x11, 0x0E), FPU2),
+        FLOOR_W_S => (fpu(0x10, 0x0F), FPU2),
+        FLOOR_W_D => (fpu(0x11, 0x0F), FPU2),
+        CVT_W_S => (fpu(0x10, 0x24), FPU2),
+        CVT_W_D => (fpu(0x11, 0x24), FPU2),
+        CVT_L_S => (fpu(0x10, 0x25), FPU2),
+        CVT_L_D => (fpu(0x11, 0x25), FPU2),
+        CVT_S_D => (fpu(0x11, 0x20), FPU2),
+        CVT_D_S => (fpu(0x10, 0x21), FPU2),
+        CVT_S_W => (fpu(0x14, 0x20), FPU2),
+        CVT_D_W => (fpu(0x14, 0x21), FPU2),
+        CVT_S_L => (fpu(0x15, 0x20), FPU2),
+        CVT_D_L => (fpu(0x15, 0x21), FPU2),
+        C_F_S => (fpu(0x10, 0x30), FPU_CMP),
+        C_F_D => (fpu(0x11, 0x30), FPU_CMP),
+        C_UN_S => (fpu(0x10, 0x31), FPU_CMP),
+        C_UN_D => (fpu(0x11, 0x31), FPU_CMP),
+        C_EQ_S => (fpu(0x10, 0x32), FPU_CMP),
+        C_EQ_D => (fpu(0x11, 0x32), FPU_CMP),
+        C_UEQ_S => (fpu(0x10, 0x33), FPU_CMP),
+        C_UEQ_D => (fpu(0x11, 0x33), FPU_CMP),
+        C_OLT_S => (fpu(0x10, 0x34), FPU_CMP),
+        C_OLT_D => (fpu(0x11, 0x34), FPU_CMP),
+        C_ULT_S => (fpu(0x10, 0x35), FPU_CMP),
+        C_ULT_D => (fpu(0x11, 0x35), FPU_CMP),
+        C_OLE_S => (fpu(0x10, 0x36), FPU_CMP),
+        C_OLE_D => (fpu(0x11, 0x36), FPU_CMP),
+        C_ULE_S => (fpu(0x10, 0x37), FPU_CMP),
+        C_ULE_D => (fpu(0x11, 0x37), FPU_CMP),
+        C_SF_S => (fpu(0x10, 0x38), FPU_CMP),
+        C_SF_D => (fpu(0x11, 0x38), FPU_CMP),
+        C_NGLE_S => (fpu(0x10, 0x39), FPU_CMP),
+        C_NGLE_D => (fpu(0x11, 0x39), FPU_CMP),
+        C_SEQ_S => (fpu(0x10, 0x3A), FPU_CMP),
+        C_SEQ_D => (fpu(0x11, 0x3A), FPU_CMP),
+        C_NGL_S => (fpu(0x10, 0x3B), FPU_CMP),
+        C_NGL_D => (fpu(0x11, 0x3B), FPU_CMP),
+        C_LT_S => (fpu(0x10, 0x3C), FPU_CMP),
+        C_LT_D => (fpu(0x11, 0x3C), FPU_CMP),
+        C_NGE_S => (fpu(0x10, 0x3D), FPU_CMP),
+        C_NGE_D => (fpu(0x11, 0x3D), FPU_CMP),
+        C_LE_S => (fpu(0x10, 0x3E), FPU_CMP),
+        C_LE_D => (fpu(0x11, 0x3E), FPU_CMP),
+        C_NGT_S => (fpu(0x10, 0x3F), FPU_CMP),
+        C_NGT_D => (fpu(0x11, 0x3F), FPU_CMP),
+        BEQL => (opcode(0x14), RS_RT_BRANCH),
+        BNEL => (opcode(0x15), RS_RT_BRANCH),
+        BLEZL => (opcode(0x16), RS_BRANCH),
+        BGTZL => (opcode(0x17), RS_BRANCH),
+        DADDI => (opcode(0x18), RT_RS_IMM),
+        DADDIU => (opcode(0x19), RT_RS_IMM),
+        LDL => (opcode(0x1A), RT_MEM),
+        LDR => (opcode(0x1B), RT_MEM),
+        
+        LB => (opcode(0x20), RT_MEM),
+        LH => (opcode(0x21), RT_MEM),
+        LWL => (opcode(0x22), RT_MEM),
+        LW => (opcode(0x23), RT_MEM),
+        LBU => (opcode(0x24), RT_MEM),
+        LHU => (opcode(0x25), RT_MEM),
+        LWR => (opcode(0x26), RT_MEM),
+        LWU => (opcode(0x27), RT_MEM),
+        SB => (opcode(0x28), RT_MEM),
+        SH => (opcode(0x29), RT_MEM),
+        SWL => (opcode(0x2A), RT_MEM),
+        SW => (opcode(0x2B), RT_MEM),
+        SDL => (opcode(0x2C), RT_MEM),
+        SDR => (opcode(0x2D), RT_MEM),
+        SWR => (opcode(0x2E), RT_MEM),
+        CACHE => (opcode(0x2F), &[CacheOp, Mem]),
+        
+        LL => (opcode(0x30), RT_MEM),
+        LWC1 => (opcode(0x31), FT_MEM),
+        LWCz => (opcode(0x30), COP_MEM),
+        LLD => (opcode(0x34), RT_MEM),
+        LDC1 => (opcode(0x35), FT_MEM),
+        LDCz => (opcode(0x34), COP_MEM),
+        LD => (opcode(0x37), RT_MEM),
+        
+        SC => (opcode(0x38), RT_MEM),
+        SWC1 => (opcode(0x39), FT_MEM),
+        SWCz => (opcode(0x38), COP_MEM),
+        SCD => (opcode(0x3C), RT_MEM),
+        SDC1 => (opcode(0x3D), FT_MEM),
+        SDCz => (opcode(0x3C), COP_MEM),
+        SD => (opcode(0x3F), RT_MEM),
+        
+        _ => return None
+    })
+}
+
+/// Fixed bits and operand slots of the operations only the RSP has, or that it encodes differently.
+fn rsp_layout(op: Operation) -> Option<(u32, &'static [Slot])> {
+    use self::{Field::*, Operation::*, Slot::*};
+    
+    Some(match op {
+        MFC0 => (cop(0, 0x00), &[Gpr(Rt), RspCp0]),
+        MTC0 => (cop(0, 0x04), &[Gpr(Rt), RspCp0]),
+        
+        MFC2 => (cop(2, 0x00), &[Gpr(Rt), VLaneMove]),
+        CFC2 => (cop(2, 0x02), &[Gpr(Rt), VCtrl]),
+        MTC2 => (cop(2, 0x04), &[Gpr(Rt), VLaneMove]),
+        CTC2 => (cop(2, 0x06), &[Gpr(Rt), VCtrl]),
+        
+        VMULF => (vu(0x00), VU3),
+        VMULU => (vu(0x01), VU3),
+        VRNDP => (vu(0x02), VU3),
+        VMULQ => (vu(0x03), VU3),
+        VMUDL => (vu(0x04), VU3),
+        VMUDM => (vu(0x05), VU3),
+        VMUDN => (vu(0x06), VU3),
+        VMUDH => (vu(0x07), VU3),
+        VMACF => (vu(0x08), VU3),
+        VMACU => (vu(0x09), VU3),
+        VRNDN => (vu(0x0A), VU3),
+        VMACQ => (vu(0x0B), VU3),
+        VMADL => (vu(0x0C), VU3),
+        VMADM => (vu(0x0D), VU3),
+        VMADN => (vu(0x0E), VU3),
+        VMADH => (vu(0x0F), VU3),
+        VADD => (vu(0x10), VU3),
+        VSUB => (vu(0x11), VU3),
+        VABS => (vu(0x13), VU3),
+        VADDC => (vu(0x14), VU3),
+        VSUBC => (vu(0x15), VU3),
+        VLT => (vu(0x20), VU3),
+        VEQ => (vu(0x21), VU3),
+        VNE => (vu(0x22), VU3),
+        VGE => (vu(0x23), VU3),
+        VCL => (vu(0x24), VU3),
+        VCH => (vu(0x25), VU3),
+        VCR => (vu(0x26), VU3),
+        VMRG => (vu(0x27), VU3),
+        VAND => (vu(0x28), VU3),
+        VNAND => (vu(0x29), VU3),
+        VOR => (vu(0x2A), VU3),
+        VNOR => (vu(0x2B), VU3),
+        VXOR => (vu(0x2C), VU3),
+        VNXOR => (vu(0x2D), VU3),
+        
+        VRCP => (vu(0x30), VU_LANE),
+        VRCPL => (vu(0x31), VU_LANE),
+        VRCPH => (vu(0x32), VU_LANE),
+        VMOV => (vu(0x33), VU_LANE),
+        VRSQ => (vu(0x34), VU_LANE),
+        VRSQL => (vu(0x35), VU_LANE),
+        VRSQH => (vu(0x36), VU_LANE),
+        VSAR => (vu(0x1D), &[VReg(Sa), VAcc]),
+        VNOP => (vu(0x37), &[]),
+        
+        LBV => (vmem(0x32, 0x00), &[VLaneMem, VMem(0)]),
+        LSV => (vmem(0x32, 0x01), &[VLaneMem, VMem(1)]),
+        LLV => (vmem(0x32, 0x02), &[VLaneMem, VMem(2)]),
+        LDV => (vmem(0x32, 0x03), &[VLaneMem, VMem(3)]),
+        LQV => (vmem(0x32, 0x04), &[VLaneMem, VMem(4)]),
+        LRV => (vmem(0x32, 0x05), &[VLaneMem, VMem(4)]),
+        LPV => (vmem(0x32, 0x06), &[VLaneMem, VMem(3)]),
+        LUV => (vmem(0x32, 0x07), &[VLaneMem, VMem(3)]),
+        LHV => (vmem(0x32, 0x08), &[VLaneMem, VMem(4)]),
+        LFV => (vmem(0x32, 0x09), &[VLaneMem, VMem(4)]),
+        LWV => (vmem(0x32, 0x0A), &[VLaneMem, VMem(4)]),
+        LTV => (vmem(0x32, 0x0B), &[VLaneMem, VMem(4)]),
+        
+        SBV => (vmem(0x3A, 0x00), &[VLaneMem, VMem(0)]),
+        SSV => (vmem(0x3A, 0x01), &[VLaneMem, VMem(1)]),
+        SLV => (vmem(0x3A, 0x02), &[VLaneMem, VMem(2)]),
+        SDV => (vmem(0x3A, 0x03), &[VLaneMem, VMem(3)]),
+        SQV => (vmem(0x3A, 0x04), &[VLaneMem, VMem(4)]),
+        SRV => (vmem(0x3A, 0x05), &[VLaneMem, VMem(4)]),
+        SPV => (vmem(0x3A, 0x06), &[VLaneMem, VMem(3)]),
+        SUV => (vmem(0x3A, 0x07), &[VLaneMem, VMem(3)]),
+        SHV => (vmem(0x3A, 0x08), &[VLaneMem, VMem(4)]),
+        SFV => (vmem(0x3A, 0x09), &[VLaneMem, VMem(4)]),
+        SWV => (vmem(0x3A, 0x0A), &[VLaneMem, VMem(4)]),
+        STV => (vmem(0x3A, 0x0B), &[VLaneMem, VMem(4)]),
+        _ => return None
+    })
+}
+
+/// Fixed bits and operand slots of an operation on the given target, or `None` if the target doesn't have it.
+pub(crate) fn layout(op: Operation, target: Target) -> Option<(u32, &'static [Slot])> {
+    match target {
+        Target::VR4300 => cpu_layout(op),
+        Target::RSP => rsp_layout(op).or_else(|| cpu_layout(op).filter(|_| is_scalar_op(op))),
+    }
+}
+
+fn branch_offset(pc: u32, addr: u32, target: Target) -> Option<u32> {
+    let diff = addr.wrapping_sub(pc.wrapping_add(4));
+    
+    // The RSP only compares the low 12 bits of the destination, so take the shortest way around IMEM.
+    let diff = match target {
+        Target::VR4300 => diff as i32,
+        Target::RSP => ((diff << 20) as i32) >> 20,
+    };
+    
+    match diff {
+        _ if diff % 4 != 0 => None,
+        _ if (-0x20000..=0x1FFFC).contains(&diff) => Some((diff >> 2) as u16 as u32),
+        _ => None
+    }
+}
+
+fn jump_field(pc: u32, addr: u32, target: Target) -> Option<u32> {
+    // Addresses without the upper 4 bits (as printed for unresolved jumps) are taken to be in the current segment.
+    let in_segment = match target {
+        Target::VR4300 => addr >> 28 == 0 || (addr ^ pc.wrapping_add(4)) >> 28 == 0,
+        Target::RSP => true,
+    };
+    
+    match addr % 4 {
+        0 if in_segment => Some((addr >> 2) & 0x3FFFFFF),
+        _ => None
+    }
+}
+
+fn encode_operand(index: usize, slot: Slot, operand: Operand, pc: Option<u32>, target: Target) -> Result<u32, EncodeError> {
+    use Operand::*;
+    
+    let out_of_range = || EncodeError::OutOfRange(index, operand);
+    let fit = |value: u32, width: u32, shift: u32| if value >> width == 0 { Ok(value << shift) } else { Err(out_of_range()) };
+    let pc = || pc.ok_or(EncodeError::Unresolved(index));
+    
+    match (slot, operand) {
+        (Slot::Gpr(field), Reg(reg)) | (Slot::Fpr(field), Cp1Reg(reg)) | (Slot::Cop(field), CopReg(reg)) | (Slot::VReg(field), VReg(reg)) => fit(reg as u32, 5, field as u32),
+        (Slot::Cp0, Cp0Reg(reg)) | (Slot::Fcr, Cp1CtrlReg(reg)) => fit(reg as u32, 5, 11),
+        (Slot::RspCp0, RspCp0Reg(reg)) => fit(reg as u32, 4, 11),
+        (Slot::VElement, VRegElement(reg, e)) => Ok(fit(reg as u32, 5, 16)? | fit(e as u32, 4, 21)?),
+        (Slot::VLaneDest, VRegLane(reg, lane)) => Ok(fit(reg as u32, 5, 6)? | fit(lane as u32, 5, 11)?),
+        (Slot::VLaneMove, VRegLane(reg, lane)) => Ok(fit(reg as u32, 5, 11)? | fit(lane as u32, 4, 7)?),
+        (Slot::VLaneMem, VRegLane(reg, lane)) => Ok(fit(reg as u32, 5, 16)? | fit(lane as u32, 4, 7)?),
+        (Slot::VCtrl, VCtrlReg(reg)) if reg < 3 => Ok((reg as u32) << 11),
+        (Slot::VAcc, VAcc(acc)) if acc < 3 => Ok((acc as u32 + 8) << 21),
+        (Slot::VCtrl, VCtrlReg(_)) | (Slot::VAcc, VAcc(_)) => Err(out_of_range()),
+        (Slot::Shift, Lit8(sa)) => fit(sa as u32, 5, 6),
+        (Slot::Imm16, Imm16(imm)) => Ok(imm as u16 as u32),
+        (Slot::Lit16, Lit16(lit)) => Ok(lit as u32),
+        (Slot::Branch, Branch(offset)) => Ok(offset as u16 as u32),
+        (Slot::Branch, Addr(addr)) => branch_offset(pc()?, addr, target).ok_or_else(out_of_range),
+        (Slot::Jump, JumpTarget(field)) => fit(field, 26, 0),
+        (Slot::Jump, Addr(addr)) => jump_field(pc()?, addr, target).ok_or_else(out_of_range),
+        (Slot::Mem, Mem(base, offset)) => Ok(fit(base as u32, 5, 21)? | offset as u16 as u32),
+        (Slot::VMem(shift), Mem(base, offset)) => {
+            let scaled = (offset as i32) >> shift;
+            
+            match scaled << shift == offset as i32 && (-0x40..=0x3F).contains(&scaled) {
+                true => Ok(fit(base as u32, 5, 21)? | (scaled as u32 & 0x7F)),
+                false => Err(out_of_range()),
+            }
+        },
+        (Slot::CacheOp, CacheOp(op)) => fit(op as u32, 5, 16),
+        (Slot::Code20, Code(code)) => fit(code, 20, 6),
+        (Slot::BreakCode, Code(code)) => fit(code, 10, 16),
+        (Slot::Code10, Code(code)) => fit(code, 10, 6),
+        (Slot::Stype, Code(code)) => fit(code, 5, 6),
+        (Slot::Cofun, Lit32(cofun)) => fit(cofun, 25, 0),
+        _ => Err(EncodeError::WrongOperand(index, operand)),
+    }
+}
+
+/// Opcode bits selecting the coprocessor of a generic operation, taken from `code`. Only combinations the decoder
+/// gives the generic operation for are accepted.
+fn coprocessor_bits(op: Operation, code: u32) -> Result<u32, EncodeError> {
+    use Operation::*;
+    
+    let z = (code >> 26) & 0x03;
+    
+    match op {
+        LWCz | LDCz | SWCz | SDCz if z != 2 => Err(EncodeError::Coprocessor(op, z as u8)),
+        MFCz | MTCz | CFCz | CTCz | COPz | BCzF | BCzFL | BCzT | BCzTL if z == 1 => Err(EncodeError::Coprocessor(op, z as u8)),
+        LWCz | LDCz | SWCz | SDCz | MFCz | MTCz | CFCz | CTCz | COPz | BCzF | BCzFL | BCzT | BCzTL => Ok(z << 26),
+        _ => Ok(0),
+    }
+}
+
+fn encode(instr: &Instruction, pc: Option<u32>, target: Target) -> Result<u32, EncodeError> {
+    let (template, slots) = layout(instr.op, target).ok_or(EncodeError::Unsupported(instr.op, target))?;
+    
+    let count = instr.args.iter().take_while(|arg| arg.is_some()).count();
+    let required = slots.iter().filter(|slot| !slot.optional()).count();
+    if count < required || count > slots.len() || instr.args[count..].iter().any(Option::is_some) {
+        return Err(EncodeError::OperandCount(instr.args.iter().flatten().count(), required, slots.len()));
+    }
+    
+    let mut code = template | coprocessor_bits(instr.op, instr.code)?;
+    for (index, (slot, arg)) in slots.iter().zip(instr.args.iter()).enumerate() {
+        if let Some(operand) = arg {
+            code |= encode_operand(index, *slot, *operand, pc, target)?;
+        }
+    }
+    
+    Ok(code)
+}
+
+impl Instruction {
+    /// Encodes this instruction for the VR4300. This is the inverse of [`crate::disassembly::disassemble`].
+    /// 
+    /// Only `op` and `args` are used, except for the generic coprocessor operations (`MFCz`, `LWCz`, etc), which take
+    /// the coprocessor number from bits 27..26 of `code`.
+    pub fn encode(&self) -> Result<u32, EncodeError> {
+        encode(self, None, Target::VR4300)
+    }
+    
+    pub fn encode_target(&self, target: Target) -> Result<u32, EncodeError> {
+        encode(self, None, target)
+    }
+    
+    /// Encodes this instruction as if it were at `pc`, which allows branch and jump destinations given as
+    /// [`Operand::Addr`] (see [`Instruction::resolve`]).
+    pub fn encode_at(&self, pc: u32, target: Target) -> Result<u32, EncodeError> {
+        encode(self, Some(pc), target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EncodeError;
+    use crate::disassembly::{disassemble_target, tests::ENCODINGS, Disassembly, Instruction, Operand::*, Operation::*, Target};
+    
+    #[test]
+    fn encodings_round_trip() {
+        for (target, code, op, _) in ENCODINGS.iter().filter(|row| row.2 != Unknown) {
+            let instr = disassemble_target(*code, *target);
+            
+            assert_eq!(instr.encode_target(*target), Ok(*code), "{} ({})", instr, op);
+        }
+    }
+    
+    #[test]
+    fn resolved_encodings_round_trip() {
+        let raw: Vec<u32> = ENCODINGS.iter().filter(|row| row.0 == Target::VR4300 && row.2 != Unknown).map(|row| row.1).collect();
+        let disasm = Disassembly::from_u32(&raw).at_vaddr(0x80000400);
+        
+        for (i, instr) in disasm.instructions.iter().enumerate() {
+            assert_eq!(instr.encode_at(disasm.addr_of(i), Target::VR4300), Ok(raw[i]), "{}", instr);
+        }
+    }
+    
+    #[test]
+    fn constructed_instructions() {
+        assert_eq!(Instruction::new3(0, ADDIU, Reg(29), Reg(29), Imm16(-0x18)).encode(), Ok(0x27BDFFE8));
+        assert_eq!(Instruction::new2(0, SW, Reg(31), Mem(29, 0x14)).encode(), Ok(0xAFBF0014));
+        assert_eq!(Instruction::new1(0, JAL, Addr(0x80000450)).encode_at(0x80000400, Target::VR4300), Ok(0x0C000114));
+        assert_eq!(Instruction::new0(0, BREAK).encode(), Ok(0x0000000D));
+        assert_eq!(Instruction::new1(0, BREAK, Code(7)).encode(), Ok(0x0007000D));
+        assert_eq!(Instruction::new2(0, BREAK, Code(1), Code(2)).encode(), Ok(0x0001008D));
+        assert_eq!(Instruction::new2(0x08000000, MFCz, Reg(8), CopReg(5)).encode(), Ok(0x48082800));
+    }
+    
+    #[test]
+    fn errors() {
+        assert_eq!(Instruction::new0(0, Unknown).encode(), Err(EncodeError::Unsupported(Unknown, Target::VR4300)));
+        assert_eq!(Instruction::new0(0, VNOP).encode(), Err(EncodeError::Unsupported(VNOP, Target::VR4300)));
+        assert_eq!(Instruction::new2(0, ADDU, Reg(1), Reg(2)).encode(), Err(EncodeError::OperandCount(2, 3, 3)));
+        assert_eq!(Instruction::new3(0, SLL, Reg(1), Reg(2), Lit8(32)).encode(), Err(EncodeError::OutOfRange(2, Lit8(32))));
+        assert_eq!(Instruction::new3(0, ADDIU, Reg(1), Reg(2), Lit16(1)).encode(), Err(EncodeError::WrongOperand(2, Lit16(1))));
+        assert_eq!(Instruction::new1(0, J, Addr(0x80000450)).encode(), Err(EncodeError::Unresolved(0)));
+        assert_eq!(Instruction::new1(0, J, Addr(0x90000450)).encode_at(0x80000400, Target::VR4300), Err(EncodeError::OutOfRange(0, Addr(0x90000450))));
+        assert_eq!(Instruction::new2(0, LWCz, CopReg(1), Mem(0, 0)).encode(), Err(EncodeError::Coprocessor(LWCz, 0)));
+    }
+}
@@ -0,0 +1,317 @@
+use crate::disassembly::{cache_op_name, Disassembly, Instruction, Operand, RegNames, RegScheme};
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Case {
+    Upper,
+    Lower,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Radix {
+    Hex,
+    Decimal,
+}
+
+/// Options for rendering instructions as text.
+/// 
+/// Start from one of the presets and override fields as needed, e.g. `Style { show_raw: false, ..Style::objdump() }`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Style {
+    pub names: RegNames,
+    /// Case of mnemonics and hex digits.
+    pub case: Case,
+    /// Radix of signed immediates, memory offsets, branch offsets, shift amounts and codes. Logical immediates
+    /// (ORI, LUI, etc) and addresses are always hex.
+    pub radix: Radix,
+    /// Wraps each column in brackets (`[0x80000400][0x27BDFFE8][ADDIU sp, sp, -0x18]`), as `Display` does.
+    pub brackets: bool,
+    /// Writes unresolved branches relative to the branch itself (`. + 8`), as GNU as reads them, rather than as the
+    /// offset from the delay slot (`+0x4`).
+    pub dot_branches: bool,
+    /// Names known CACHE ops (`Hit_Writeback_Invalidate_D`). GNU as only takes the number.
+    pub cache_op_names: bool,
+    pub show_addr: bool,
+    pub show_raw: bool,
+    /// Spaces before the mnemonic, when neither the address nor the raw word are shown.
+    pub indent: usize,
+    /// Minimum width of the mnemonic column. Ignored with `brackets`.
+    pub mnemonic_width: usize,
+    /// Placed between operands.
+    pub separator: &'static str,
+    /// Column that comments are aligned to. Comments always get at least one space before them.
+    pub comment_column: usize,
+    pub comment_prefix: &'static str,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style::compact()
+    }
+}
+
+impl Style {
+    /// Our own style, identical to the `Display` impls.
+    pub fn compact() -> Style {
+        Style {
+            names: RegNames::default(),
+            case: Case::Upper,
+            radix: Radix::Hex,
+            brackets: true,
+            dot_branches: false,
+            cache_op_names: true,
+            show_addr: true,
+            show_raw: true,
+            indent: 0,
+            mnemonic_width: 0,
+            separator: ", ",
+            comment_column: 0,
+            comment_prefix: ";",
+        }
+    }
+    
+    /// Similar to `objdump -d` (`80000400:   27bdffe8    addiu   sp,sp,-24`).
+    pub fn objdump() -> Style {
+        Style {
+            names: RegNames::new(RegScheme::O32, false),
+            case: Case::Lower,
+            radix: Radix::Decimal,
+            brackets: false,
+            dot_branches: false,
+            cache_op_names: true,
+            show_addr: true,
+            show_raw: true,
+            indent: 0,
+            mnemonic_width: 8,
+            separator: ",",
+            comment_column: 48,
+            comment_prefix: "#",
+        }
+    }
+    
+    /// Source that GNU as, and our own assembler, will accept.
+    pub fn gnu_as() -> Style {
+        Style {
+            names: RegNames::new(RegScheme::O32, true),
+            case: Case::Lower,
+            radix: Radix::Decimal,
+            brackets: false,
+            dot_branches: true,
+            cache_op_names: false,
+            show_addr: false,
+            show_raw: false,
+            indent: 4,
+            mnemonic_width: 8,
+            separator: ", ",
+            comment_column: 40,
+            comment_prefix: "#",
+        }
+    }
+    
+    fn hex(&self, value: u32, width: usize) -> String {
+        match self.case {
+            Case::Upper => format!("{:#0width$X}", value, width = width),
+            Case::Lower => format!("{:#0width$x}", value, width = width),
+        }
+    }
+    
+    fn unsigned(&self, value: u32, width: usize) -> String {
+        match self.radix {
+            Radix::Hex => self.hex(value, width),
+            Radix::Decimal => value.to_string(),
+        }
+    }
+    
+    fn signed(&self, value: i32, width: usize) -> String {
+        match value {
+            _ if value < 0 => format!("-{}", self.unsigned(value.unsigned_abs(), width)),
+            _ => self.unsigned(value as u32, width),
+        }
+    }
+    
+    pub fn operand(&self, operand: &Operand) -> String {
+        use Operand::*;
+        
+        match *operand {
+            JumpTarget(field) => self.hex(field << 2, 9),
+            Branch(offset) if self.dot_branches => match offset as i32 * 4 + 4 {
+                from_dot if from_dot < 0 => format!(". - {}", self.unsigned(from_dot.unsigned_abs(), 0)),
+                from_dot => format!(". + {}", self.unsigned(from_dot as u32, 0)),
+            },
+            Branch(offset) if offset < 0 => self.signed(offset as i32 * 4, 6),
+            Branch(offset) => format!("+{}", self.signed(offset as i32 * 4, 6)),
+            Addr(addr) => self.hex(addr, 10),
+            Mem(base, offset) => format!("{}({})", self.signed(offset as i32, 0), self.names.cpu(base)),
+            Imm16(val) => self.signed(val as i32, 0),
+            CacheOp(op) if !self.cache_op_names || cache_op_name(op).is_none() => self.hex(op as u32, 4),
+            Code(val) => self.unsigned(val, 0),
+            Lit8(val) => self.unsigned(val as u32, 4),
+            Lit16(val) => self.hex(val as u32, 6),
+            Lit32(val) => self.hex(val, 10),
+            _ => operand.display(self.names).to_string(),
+        }
+    }
+    
+    /// The mnemonic and operands, without any other columns.
+    pub fn instruction(&self, instr: &Instruction) -> String {
+        let mnemonic = match self.case {
+            Case::Upper => instr.op.to_string(),
+            Case::Lower => instr.op.to_string().to_lowercase(),
+        };
+        let args = instr.args.iter().flatten().map(|arg| self.operand(arg)).collect::<Vec<String>>().join(self.separator);
+        
+        match (self.brackets, args.is_empty()) {
+            (true, _) => format!("{} {}", mnemonic, args),
+            (false, true) => mnemonic,
+            (false, false) => format!("{:width$} {}", mnemonic, args, width = self.mnemonic_width.saturating_sub(1)),
+        }
+    }
+    
+    /// A full line for the instruction at `addr`, with an optional comment.
+    pub fn line(&self, addr: u32, instr: &Instruction, comment: Option<&str>) -> String {
+        let mut out = String::new();
+        
+        if self.brackets {
+            if self.show_addr {
+                out.push_str(&format!("[{}]", self.hex(addr, 10)));
+            }
+            if self.show_raw {
+                out.push_str(&format!("[{}]", self.hex(instr.code, 10)));
+            }
+            out.push_str(&format!("[{}]", self.instruction(instr)));
+        } else {
+            if self.show_addr {
+                out.push_str(&format!("{}:   ", &self.hex(addr, 10)[2..]));
+            }
+            if self.show_raw {
+                out.push_str(&format!("{}    ", &self.hex(instr.code, 10)[2..]));
+            }
+            if !self.show_addr && !self.show_raw {
+                out.push_str(&" ".repeat(self.indent));
+            }
+            out.push_str(&self.instruction(instr));
+        }
+        
+        if let Some(comment) = comment {
+            let len = out.chars().count();
+            out.push_str(&" ".repeat(self.comment_column.saturating_sub(len).max(1)));
+            out.push_str(&format!("{} {}", self.comment_prefix, comment));
+        }
+        
+        out
+    }
+}
+
+/// Supplies comments for instructions, such as symbol names or resolved branch targets.
+pub trait Annotator {
+    fn annotate(&self, addr: u32, instr: &Instruction) -> Option<String>;
+}
+
+impl<F: Fn(u32, &Instruction) -> Option<String>> Annotator for F {
+    fn annotate(&self, addr: u32, instr: &Instruction) -> Option<String> {
+        self(addr, instr)
+    }
+}
+
+/// Comments keyed by the address of the instruction they belong to.
+impl Annotator for HashMap<u32, String> {
+    fn annotate(&self, addr: u32, _: &Instruction) -> Option<String> {
+        self.get(&addr).cloned()
+    }
+}
+
+impl Disassembly {
+    pub fn render(&self, style: &Style) -> String {
+        self.render_annotated(style, &|_: u32, _: &Instruction| None)
+    }
+    
+    /// Renders every instruction on its own line, with comments from `annotator`.
+    pub fn render_annotated(&self, style: &Style, annotator: &dyn Annotator) -> String {
+        let mut out = String::new();
+        for (i, instr) in self.instructions.iter().enumerate() {
+            let addr = self.addr_of(i);
+            
+            out.push_str(&style.line(addr, instr, annotator.annotate(addr, instr).as_deref()));
+            out.push('\n');
+        }
+        
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Case, Radix, Style};
+    use crate::assembler::assemble;
+    use crate::disassembly::{disassemble_target, tests::ENCODINGS, Disassembly, Instruction, Operation::*, Target};
+    use std::collections::HashMap;
+    
+    #[test]
+    fn compact_matches_display() {
+        let style = Style { show_addr: false, ..Style::compact() };
+        
+        for (target, code, _, _) in ENCODINGS {
+            let instr = disassemble_target(*code, *target);
+            
+            assert_eq!(style.line(0, &instr, None), instr.to_string());
+        }
+    }
+    
+    #[test]
+    fn presets() {
+        let disasm = Disassembly::from_u32(&[0x27BDFFE8, 0xAFBF0014, 0x0C000114, 0x00000000]).at_vaddr(0x80000400);
+        let mut comments = HashMap::new();
+        comments.insert(0x80000408, String::from("call"));
+        
+        assert_eq!(disasm.render_annotated(&Style::compact(), &comments), "\
+[0x80000400][0x27BDFFE8][ADDIU sp, sp, -0x18]
+[0x80000404][0xAFBF0014][SW ra, 0x14(sp)]
+[0x80000408][0x0C000114][JAL 0x80000450] ; call
+[0x8000040C][0x00000000][NOP ]
+");
+        assert_eq!(disasm.render_annotated(&Style::objdump(), &comments), "\
+80000400:   27bdffe8    addiu   sp,sp,-24
+80000404:   afbf0014    sw      ra,20(sp)
+80000408:   0c000114    jal     0x80000450      # call
+8000040c:   00000000    nop
+");
+        assert_eq!(disasm.render(&Style::gnu_as()), "    addiu   $sp, $sp, -24\n    sw      $ra, 20($sp)\n    jal     0x80000450\n    nop\n");
+        
+        let style = Style { case: Case::Upper, radix: Radix::Hex, mnemonic_width: 0, ..Style::gnu_as() };
+        assert_eq!(style.line(0, &disasm.instructions[0], None), "    ADDIU $sp, $sp, -0x18");
+    }
+    
+    #[test]
+    fn gnu_as_syntax() {
+        let style = Style::gnu_as();
+        
+        for (code, line) in &[
+            (0x40086000, "    mfc0    $t0, $12"),
+            (0x4448F800, "    cfc1    $t0, $31"),
+            (0x1085FFFF, "    beq     $a0, $a1, . + 0"),
+            (0x1085FFFD, "    beq     $a0, $a1, . - 8"),
+            (0x10850003, "    beq     $a0, $a1, . + 16"),
+            (0xBC950000, "    cache   0x15, 0($a0)"),
+        ] {
+            let instr = disassemble_target(*code, Target::VR4300);
+            
+            assert_eq!(style.line(0, &instr, None), *line);
+            assert_eq!(assemble(line, 0x80000400).unwrap().bytes, code.to_be_bytes());
+        }
+    }
+    
+    #[test]
+    fn gnu_as_output_assembles() {
+        let raw: Vec<u32> = ENCODINGS.iter()
+            .filter(|row| row.0 == Target::VR4300 && !matches!(row.2, MFCz | MTCz | CFCz | CTCz | COPz | LWCz | LDCz | SWCz | SDCz | BCzF | BCzFL | BCzT | BCzTL | Unknown))
+            .map(|row| row.1)
+            .collect();
+        
+        for disasm in &[Disassembly::from_u32(&raw), Disassembly::from_u32(&raw).at_vaddr(0x80000400)] {
+            let annotate = |addr: u32, _: &Instruction| Some(format!("{:#X}", addr));
+            let source = disasm.render_annotated(&Style::gnu_as(), &annotate);
+            
+            assert!(Disassembly::from_u8(&assemble(&source, disasm.addr_of(0)).unwrap().bytes) == *disasm);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::rom::Rom;
+use std::fmt::{Display, Formatter};
+
+/// The hashes DAT files identify ROMs by.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Hashes {
+    pub size: usize,
+    pub crc32: u32,
+    pub md5: [u8; 16],
+    pub sha1: [u8; 20],
+}
+
+impl Hashes {
+    pub fn of(bytes: &[u8]) -> Hashes {
+        Hashes {
+            size: bytes.len(),
+            crc32: crc32fast::hash(bytes),
+            md5: md5::compute(bytes).0,
+            sha1: sha1_smol::Sha1::from(bytes).digest().bytes(),
+        }
+    }
+    
+    pub fn md5_hex(&self) -> String {
+        to_hex(&self.md5)
+    }
+    
+    pub fn sha1_hex(&self) -> String {
+        to_hex(&self.sha1)
+    }
+}
+
+impl Display for Hashes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "size {} crc32 {:08x} md5 {} sha1 {}", self.size, self.crc32, self.md5_hex(), self.sha1_hex())
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Parses a hex digest, e.g. the `md5` and `sha1` attributes of a DAT entry. Case is ignored.
+pub(crate) fn from_hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+    if text.len() != N * 2 || !text.is_ascii() {
+        return None;
+    }
+    
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[(i * 2)..(i * 2 + 2)], 16).ok()?;
+    }
+    
+    Some(bytes)
+}
+
+impl Rom {
+    /// Hashes of the big-endian (`.z64`) image, which is what No-Intro DATs list.
+    pub fn hashes(&self) -> Hashes {
+        Hashes::of(self.data())
+    }
+    
+    /// Hashes of the image in the byte order it was loaded in, i.e. of the original file.
+    pub fn original_hashes(&self) -> Hashes {
+        Hashes::of(&self.to_bytes(self.byte_order))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_hex, Hashes};
+    use crate::rom::{test_rom, ByteOrder, Rom};
+    
+    #[test]
+    fn known_digests() {
+        let hashes = Hashes::of(b"The quick brown fox jumps over the lazy dog");
+        
+        assert_eq!(hashes.size, 43);
+        assert_eq!(hashes.crc32, 0x414FA339);
+        assert_eq!(hashes.md5_hex(), "9e107d9d372bb6826bd81d3542a419d6");
+        assert_eq!(hashes.sha1_hex(), "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12");
+        assert_eq!(from_hex::<16>("9E107D9D372BB6826BD81D3542A419D6"), Some(hashes.md5));
+        assert_eq!(from_hex::<16>("9e107d9d"), None);
+        assert_eq!(from_hex::<4>("9e10zz9d"), None);
+    }
+    
+    #[test]
+    fn normalized_and_original() {
+        let mut bytes = test_rom(0x1000).into_bytes();
+        let z64 = Hashes::of(&bytes);
+        
+        ByteOrder::ByteSwapped.swap(&mut bytes);
+        let rom = Rom::new(bytes.clone()).unwrap();
+        
+        assert_eq!(rom.hashes(), z64);
+        assert_eq!(rom.original_hashes(), Hashes::of(&bytes));
+        assert_ne!(rom.hashes(), rom.original_hashes());
+    }
+}
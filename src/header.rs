@@ -0,0 +1,259 @@
+use crate::rom::Header;
+
+/// What kind of media a game code describes, from its first character.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum MediaType {
+    /// `N`
+    Cartridge,
+    /// `D`, a 64DD disk.
+    Disk,
+    /// `C`, a cartridge that can be expanded with a 64DD disk.
+    ExpandableCartridge,
+    /// `E`, a 64DD disk that expands a cartridge.
+    DiskExpansion,
+    /// `Z`, an Aleck64 arcade board.
+    Aleck64,
+    Unknown(u8),
+}
+
+impl From<u8> for MediaType {
+    fn from(code: u8) -> Self {
+        match code {
+            b'N' => MediaType::Cartridge,
+            b'D' => MediaType::Disk,
+            b'C' => MediaType::ExpandableCartridge,
+            b'E' => MediaType::DiskExpansion,
+            b'Z' => MediaType::Aleck64,
+            _ => MediaType::Unknown(code),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone, strum_macros::Display)]
+pub enum TvStandard {
+    NTSC,
+    PAL,
+    MPAL,
+}
+
+/// Destination region, from the last character of a game code.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Region {
+    /// `7`
+    Beta,
+    /// `A`
+    Asia,
+    /// `B`
+    Brazil,
+    /// `C`
+    China,
+    /// `D`
+    Germany,
+    /// `E`
+    NorthAmerica,
+    /// `F`
+    France,
+    /// `G`
+    Gateway64NTSC,
+    /// `H`
+    Netherlands,
+    /// `I`
+    Italy,
+    /// `J`
+    Japan,
+    /// `K`
+    Korea,
+    /// `L`
+    Gateway64PAL,
+    /// `N`
+    Canada,
+    /// `P`, `X` or `Y`. Multi-language European releases use all three.
+    Europe(u8),
+    /// `S`
+    Spain,
+    /// `U`
+    Australia,
+    /// `W`
+    Scandinavia,
+    Unknown(u8),
+}
+
+impl From<u8> for Region {
+    fn from(code: u8) -> Self {
+        use Region::*;
+        
+        match code {
+            b'7' => Beta,
+            b'A' => Asia,
+            b'B' => Brazil,
+            b'C' => China,
+            b'D' => Germany,
+            b'E' => NorthAmerica,
+            b'F' => France,
+            b'G' => Gateway64NTSC,
+            b'H' => Netherlands,
+            b'I' => Italy,
+            b'J' => Japan,
+            b'K' => Korea,
+            b'L' => Gateway64PAL,
+            b'N' => Canada,
+            b'P' | b'X' | b'Y' => Europe(code),
+            b'S' => Spain,
+            b'U' => Australia,
+            b'W' => Scandinavia,
+            _ => Unknown(code),
+        }
+    }
+}
+
+impl Region {
+    /// The character used in game codes.
+    pub fn code(self) -> u8 {
+        use Region::*;
+        
+        match self {
+            Beta => b'7',
+            Asia => b'A',
+            Brazil => b'B',
+            China => b'C',
+            Germany => b'D',
+            NorthAmerica => b'E',
+            France => b'F',
+            Gateway64NTSC => b'G',
+            Netherlands => b'H',
+            Italy => b'I',
+            Japan => b'J',
+            Korea => b'K',
+            Gateway64PAL => b'L',
+            Canada => b'N',
+            Europe(code) | Unknown(code) => code,
+            Spain => b'S',
+            Australia => b'U',
+            Scandinavia => b'W',
+        }
+    }
+    
+    /// The TV standard consoles sold in this region use. `None` for betas and unknown regions.
+    pub fn tv_standard(self) -> Option<TvStandard> {
+        use Region::*;
+        
+        match self {
+            Asia | NorthAmerica | Gateway64NTSC | Japan | Korea | Canada => Some(TvStandard::NTSC),
+            Brazil => Some(TvStandard::MPAL),
+            China | Germany | France | Netherlands | Italy | Gateway64PAL | Europe(_) | Spain | Australia | Scandinavia => Some(TvStandard::PAL),
+            Beta | Unknown(_) => None,
+        }
+    }
+}
+
+/// PI bus timing for domain 1, which the PIF ROM loads from the first word of the header before reading the rest of
+/// the cartridge. Each field is the raw register value.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct PiTiming {
+    /// `PI_BSD_DOM1_LAT`, bits 7..0. Cycles to wait after the address is latched, minus one.
+    pub latency: u8,
+    /// `PI_BSD_DOM1_PWD`, bits 15..8. Width of the read/write strobe in cycles, minus one.
+    pub pulse_width: u8,
+    /// `PI_BSD_DOM1_PGS`, bits 19..16. Pages are `2^(page_size + 2)` bytes.
+    pub page_size: u8,
+    /// `PI_BSD_DOM1_RLS`, bits 21..20. Cycles between the strobe going high and the next one, minus one.
+    pub release: u8,
+}
+
+impl PiTiming {
+    /// Size of a page in bytes. Accesses that cross a page boundary have to latch a new address.
+    pub fn page_bytes(&self) -> u32 {
+        1 << (self.page_size + 2)
+    }
+}
+
+impl Header {
+    pub fn media_type(&self) -> MediaType {
+        MediaType::from(self.game_code()[0])
+    }
+    
+    /// The 2 characters in the middle of the game code (e.g. `SM` for Super Mario 64).
+    pub fn game_id(&self) -> [u8; 2] {
+        self.cart_id.to_be_bytes()
+    }
+    
+    pub fn region(&self) -> Region {
+        Region::from(self.game_code()[3])
+    }
+    
+    pub fn tv_standard(&self) -> Option<TvStandard> {
+        self.region().tv_standard()
+    }
+    
+    pub fn pi_timing(&self) -> PiTiming {
+        PiTiming {
+            latency: self.pi_regs as u8,
+            pulse_width: (self.pi_regs >> 8) as u8,
+            page_size: ((self.pi_regs >> 16) & 0x0F) as u8,
+            release: ((self.pi_regs >> 20) & 0x03) as u8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MediaType, PiTiming, Region, TvStandard};
+    use crate::rom::{test_rom, HeaderError, Rom};
+    
+    fn rom() -> Rom {
+        test_rom(0x1000).with_bytes(0x20, b"SUPER MARIO 64      ").with_bytes(0x3B, b"NSME\x00").rom()
+    }
+    
+    #[test]
+    fn game_code() {
+        let mut rom = rom();
+        let header = rom.header();
+        
+        assert_eq!(header.media_type(), MediaType::Cartridge);
+        assert_eq!(header.game_id(), *b"SM");
+        assert_eq!(header.region(), Region::NorthAmerica);
+        assert_eq!(header.tv_standard(), Some(TvStandard::NTSC));
+        assert_eq!(header.version(), 0);
+        
+        for (code, media, region, tv) in &[
+            ("DSMJ", MediaType::Disk, Region::Japan, Some(TvStandard::NTSC)),
+            ("CSMP", MediaType::ExpandableCartridge, Region::Europe(b'P'), Some(TvStandard::PAL)),
+            ("ESMB", MediaType::DiskExpansion, Region::Brazil, Some(TvStandard::MPAL)),
+            ("ZSM7", MediaType::Aleck64, Region::Beta, None),
+            ("QSMQ", MediaType::Unknown(b'Q'), Region::Unknown(b'Q'), None),
+        ] {
+            rom.set_game_code(code).unwrap();
+            let header = rom.header();
+            
+            assert_eq!(header.media_type(), *media);
+            assert_eq!(header.region(), *region);
+            assert_eq!(header.region().code(), code.as_bytes()[3]);
+            assert_eq!(header.tv_standard(), *tv);
+        }
+    }
+    
+    #[test]
+    fn pi_timing() {
+        let timing = rom().header().pi_timing();
+        
+        assert_eq!(timing, PiTiming { latency: 0x40, pulse_width: 0x12, page_size: 7, release: 3 });
+        assert_eq!(timing.page_bytes(), 512);
+    }
+    
+    #[test]
+    fn shift_jis_names() {
+        let mut rom = rom();
+        assert_eq!(rom.header().name(), "SUPER MARIO 64");
+        
+        rom.set_name("ﾏﾘｵｶｰﾄ64").unwrap();
+        assert_eq!(rom.data()[0x20..0x34], *b"\xCF\xD8\xB5\xB6\xB0\xC4\x36\x34            ");
+        assert_eq!(rom.header().name(), "ﾏﾘｵｶｰﾄ64");
+        
+        rom.set_name("ゼルダの伝説").unwrap();
+        assert_eq!(rom.data()[0x20..0x2C], *b"\x83\x5B\x83\x8B\x83\x5F\x82\xCC\x93\x60\x90\xE0");
+        assert_eq!(rom.header().name(), "ゼルダの伝説");
+        
+        assert_eq!(rom.set_name("ゼルダの伝説　時のオカリナ"), Err(HeaderError::NameTooLong(26)));
+        assert_eq!(rom.set_name("Ω€"), Err(HeaderError::Unencodable(String::from("Ω€"))));
+    }
+}
@@ -0,0 +1,21 @@
+extern crate strum;
+extern crate strum_macros;
+
+pub mod rom;
+pub mod disassembly;
+pub mod rsp;
+pub mod pseudo;
+pub mod strict;
+pub mod semantics;
+pub mod assembler;
+pub mod encoder;
+pub mod format;
+pub mod cic;
+pub mod checksum;
+pub mod header;
+pub mod boot;
+pub mod hash;
+pub mod dat;
+pub mod table;
+pub mod savetype;
+pub mod disk;
@@ -1,27 +1,27 @@
-
-extern crate strum;
-extern crate strum_macros;
-
-mod rom;
-mod disassembly;
-
 use std::path::{Path, PathBuf};
-use crate::disassembly::Disassembly;
-use std::fs::{File, FileType};
+use parse64::disassembly::{Disassembly, Target};
+use parse64::format::Style;
+use std::fs::File;
 use std::io::Write;
-use crate::rom::{Header, Rom};
+use parse64::rom::{ByteOrder, Header, Rom, RomError};
+use std::error::Error;
+use std::collections::HashMap;
+use parse64::cic::{CicFamily, CicTable};
+use parse64::dat::Dat;
+use parse64::disk::DiskImage;
 
 
-fn main() {
+fn main() -> Result<(), Box<dyn Error>> {
     //save_disassembly(disassemble_ipl3_headerless("/data/storage/roms/n64-nointro/Star Fox 64 (USA).z64"), "/data/storage/roms/n64-nointro/Star Fox 64 (USA).z64.IPL3.disasm");
     //save_disassembly(disassemble_ipl3_headerless("/data/storage/roms/n64-nointro/Lylat Wars (Europe) (En,Fr,De).z64"), "/data/storage/roms/n64-nointro/Lylat Wars (Europe) (En,Fr,De).z64.IPL3.disasm");
+    //save_disassembly(disassemble_boot_segment("/data/storage/roms/n64-nointro/Star Fox 64 (USA).z64")?, "/data/storage/roms/n64-nointro/Star Fox 64 (USA).z64.boot.disasm")?;
     //save_disassembly(disassemble_pifrom("/data/storage/preservation/pifdata.bin"), "/data/storage/preservation/pifdata.bin.disasm");
     //save_disassembly(disassemble_ipl3_headerless("/data/storage/roms/n64-nointro/Conker's Bad Fur Day (USA).z64"), "/data/storage/roms/n64-nointro/Conker's Bad Fur Day (USA).z64.IPL3.disasm");
-    save_disassembly(disassemble_pifrom("/data/storage/roms/n64-nointro/Namco Museum 64 (USA).z64"), "/data/storage/roms/n64-nointro/Namco Museum 64 (USA).z64.disasm");
+    save_disassembly(disassemble_pifrom("/data/storage/roms/n64-nointro/Namco Museum 64 (USA).z64")?, "/data/storage/roms/n64-nointro/Namco Museum 64 (USA).z64.disasm")?;
     
     /*let mut count = 0;
     let mut disasms = Vec::new();
-    let dirs = std::fs::read_dir("/data/storage/roms/n64-nointro/").unwrap().collect::<Result<Vec<_>, std::io::Error>>().unwrap();
+    let dirs = std::fs::read_dir("/data/storage/roms/n64-nointro/")?.collect::<Result<Vec<_>, std::io::Error>>()?;
     let dirs_len = dirs.len();
     for entry in dirs {
         let file_type = entry.file_type();
@@ -29,7 +29,7 @@ fn main() {
         let file_name = String::from(file_name_os.to_str().unwrap());
         
         if file_type.is_ok() && file_type.unwrap().is_file() && file_name.ends_with(".z64") {
-            disasms.push((disassemble_ipl3_headerless(entry.path().to_str().unwrap()), file_name));
+            disasms.push((disassemble_ipl3_headerless(entry.path().to_str().unwrap())?, file_name));
         }
         
         count += 1;
@@ -66,10 +66,18 @@ fn main() {
         }
     }*/
     
-    //dump_headers("/data/storage/roms/n64-nointro");
+    //dump_headers("/data/storage/roms/n64-nointro")?;
+    //count_cics("/data/storage/roms/n64-nointro")?;
+    //identify_roms("/data/storage/roms/n64-nointro", "/data/storage/dats/Nintendo - Nintendo 64 (BigEndian).dat")?;
+    //detect_save_types("/data/storage/roms/n64-nointro")?;
+    //save_disassembly(disassemble_disk_ipl("/data/storage/roms/n64dd/F-Zero X Expansion Kit (Japan).ndd")?, "/data/storage/roms/n64dd/F-Zero X Expansion Kit (Japan).ndd.IPL.disasm")?;
+    //save_disassembly(disassemble_ddipl("/data/storage/preservation/64DD IPL (Japan).n64")?, "/data/storage/preservation/64DD IPL (Japan).n64.disasm")?;
+    
+    Ok(())
 }
 
-fn dump_headers(path_str: &str) {
+#[allow(dead_code)]
+fn dump_headers(path_str: &str) -> Result<(), Box<dyn Error>> {
     let path = Path::new(path_str);
     
     let mut valid_counter = 0;
@@ -78,16 +86,13 @@ fn dump_headers(path_str: &str) {
     
     let mut out_path = String::new();
     let mut valid_paths: Vec<PathBuf> = Vec::new();
-    let mut headers: Vec<Header> = Vec::new();
+    let mut headers: Vec<(&Path, Header, ByteOrder)> = Vec::new();
     if path.is_dir() {
-        let dir = path.read_dir().unwrap();
-        
-        for wrapped_entry in dir {
-            let entry = wrapped_entry.unwrap();
-            let path = entry.path();
+        for entry in path.read_dir()? {
+            let path = entry?.path();
             let extension = path.extension();
             
-            if !path.is_dir() && extension.is_some() && extension.unwrap().eq("z64") {
+            if !path.is_dir() && extension.and_then(|ext| ext.to_str()).and_then(ByteOrder::from_extension).is_some() {
                 valid_paths.push(path);
                 valid_counter += 1;
             }
@@ -105,100 +110,173 @@ fn dump_headers(path_str: &str) {
     println!("Valid Paths: {}", valid_counter);
     
     for path in &valid_paths {
-        let read_result = std::fs::read(path);
-        if read_result.is_ok() {
-            let rom = Rom::new(read_result.unwrap());
-            
-            headers.push(rom.header);
-            
-            header_counter += 1;
-            println!("Headers Extracted: {} of {}", header_counter, valid_counter);
+        match std::fs::read(path) {
+            Ok(bytes) => {
+                let (rom, problems) = Rom::lenient(bytes);
+                for problem in problems {
+                    println!("{}: {}", path.display(), problem);
+                }
+                
+                headers.push((path, rom.header().clone(), rom.byte_order));
+                
+                header_counter += 1;
+                println!("Headers Extracted: {} of {}", header_counter, valid_counter);
+            },
+            Err(err) => println!("{}: {}", path.display(), err),
         }
     }
     
-    fn u8arr_str(val: &[u8]) -> String { 
-        let result = String::from_utf8(val.to_vec());
+    let mut csv = String::from("file_name;pi_regs;clockrate;pc;release;crc1;crc2;unknown0;image_name;unknown1;media_type;game_id;region;tv_standard;version;byte_order\n");
+    for (path, header, byte_order) in headers {
+        let file_name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        let timing = header.pi_timing();
         
-        if result.is_ok() {
-            return result.unwrap();
+        csv.push_str(&format!("{};LAT={:#04X} PWD={:#04X} PGS={} RLS={};{:#010X};{:#010X};{:#010X};{:#010X};{:#010X};{:#018X};{};{:#010X};{:?};{};{:?};{};{};{}\n",
+            file_name,
+            timing.latency,
+            timing.pulse_width,
+            timing.page_size,
+            timing.release,
+            header.clockrate,
+            header.pc,
+            header.release,
+            header.crc1,
+            header.crc2,
+            header.unknown0,
+            header.name(),
+            header.unknown1,
+            header.media_type(),
+            String::from_utf8_lossy(&header.game_id()),
+            header.region(),
+            header.tv_standard().map(|tv| tv.to_string()).unwrap_or_default(),
+            header.version(),
+            byte_order.extension(),
+        ));
+        
+        save_counter += 1;
+        println!("Headers Parsed: {} of {}", save_counter, valid_counter);
+    }
+    
+    let mut out = File::create(out_path)?;
+    out.write_all(csv.as_bytes())?;
+    println!("Complete!");
+    
+    Ok(())
+}
+
+/// Groups every ROM in `dir_str` by CIC family, listing the bootcode hash of any that aren't recognized.
+#[allow(dead_code)]
+fn count_cics(dir_str: &str) -> Result<(), Box<dyn Error>> {
+    let table = CicTable::builtin();
+    let mut families: HashMap<CicFamily, usize> = HashMap::new();
+    
+    for entry in Path::new(dir_str).read_dir()? {
+        let path = entry?.path();
+        if path.is_dir() || path.extension().and_then(|ext| ext.to_str()).and_then(ByteOrder::from_extension).is_none() {
+            continue;
         }
         
-        let mut out = String::from("0x");
-        for b in val {
-            out.push_str(&format!("{:02X}", b));
+        let rom = Rom::lenient(std::fs::read(&path)?).0;
+        let family = rom.cic_with(&table);
+        if family == CicFamily::Unknown {
+            println!("{}: unknown bootcode {:#010X}", path.display(), rom.bootcode_hash());
         }
         
-        out
+        *families.entry(family).or_insert(0) += 1;
     }
-    fn u32_str(val: u32) -> String {
-        let result = String::from_utf8(val.to_be_bytes().to_vec());
-        
-        if result.is_ok() {
-            return result.unwrap();
+    
+    for (family, count) in families {
+        println!("{}: {}", family, count);
+    }
+    
+    Ok(())
+}
+
+/// Looks up every ROM in `dir_str` in a DAT file, reporting its canonical title and status, or its hashes if it isn't
+/// listed.
+#[allow(dead_code)]
+fn identify_roms(dir_str: &str, dat_path: &str) -> Result<(), Box<dyn Error>> {
+    let dat = Dat::load(dat_path)?;
+    
+    for entry in Path::new(dir_str).read_dir()? {
+        let path = entry?.path();
+        if path.is_dir() || path.extension().and_then(|ext| ext.to_str()).and_then(ByteOrder::from_extension).is_none() {
+            continue;
         }
         
-        format!("{:#010X}", val)
-    }
-    fn u16_str(val: u16) -> String {
-        let result = String::from_utf8(val.to_be_bytes().to_vec());
+        let rom = Rom::lenient(std::fs::read(&path)?).0;
+        let id = dat.identify(&rom);
         
-        if result.is_ok() {
-            return result.unwrap();
+        match id.title() {
+            Some(title) => println!("{}: {}{}{}", path.display(), title, if id.is_bad() { " [bad dump]" } else { "" }, if id.is_overdump() { " [overdump]" } else { "" }),
+            None => println!("{}: no match, {}", path.display(), rom.hashes()),
         }
-        
-        format!("{:#06X}", val)
     }
     
-    let mut csv = String::from("file_name;pi_regs;clockrate;pc;release;crc1;crc2;unknown0;image_name;unknown1;manufacturer_id;cart_id;country\n");
-    for header in headers {
-        let file_name = valid_paths[save_counter].file_name().unwrap().to_os_string();
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn detect_save_types(dir_str: &str) -> Result<(), Box<dyn Error>> {
+    for entry in Path::new(dir_str).read_dir()? {
+        let path = entry?.path();
+        if path.is_dir() || path.extension().and_then(|ext| ext.to_str()).and_then(ByteOrder::from_extension).is_none() {
+            continue;
+        }
         
-        csv.push_str(&format!("{};{:#010X};{:#010X};{:#010X};{:#010X};{:#010X};{:#010X};{:#018X};{};{:#010X};{};{};{}\n",
-            file_name.to_str().unwrap(),
-            header.pi_regs,
-            header.clockrate,
-            header.pc,
-            header.release,
-            header.crc1,
-            header.crc2,
-            header.unknown0,
-            u8arr_str(&header.image_name),
-            header.unknown1,
-            u32_str(header.manu_id),
-            u16_str(header.cart_id),
-            u16_str(header.country),
-        ));
+        let rom = Rom::lenient(std::fs::read(&path)?).0;
+        let detections = rom.save_types();
         
-        save_counter += 1;
-        println!("Headers Parsed: {} of {}", save_counter, valid_counter);
+        println!("{}: {}", path.display(), if detections.is_empty() { "none found" } else { "" });
+        for detection in detections {
+            let evidence: Vec<String> = detection.evidence.iter().map(|evidence| evidence.to_string()).collect();
+            println!("    {} ({:?}): {}", detection.save_type, detection.confidence, evidence.join(", "));
+        }
     }
     
-    let mut out = File::create(out_path).unwrap();
-    out.write_all(csv.as_bytes()).unwrap();
-    println!("Complete!");
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn disassemble_ipl3_headerless(path: &str) -> Result<Disassembly, RomError> {
+    Ok(Rom::open(path)?.bootcode_disassembly())
 }
 
-fn disassemble_ipl3_headerless(path: &str) -> Disassembly {
-    let bytes = std::fs::read(Path::new(path)).unwrap();
+#[allow(dead_code)]
+fn disassemble_ipl3_withhead(path: &str) -> Result<Disassembly, RomError> {
+    let rom = Rom::open(path)?;
     
-    Disassembly::from_u8(&Vec::from(bytes.split_at(0x1000).0.split_at(0x40).1))
+    Ok(Disassembly::from_rom(rom.data(), 0, 0x1000, 0xA4000000, Target::VR4300))
+}
+
+#[allow(dead_code)]
+fn disassemble_boot_segment(path: &str) -> Result<Disassembly, RomError> {
+    Ok(Rom::open(path)?.boot_segment())
 }
 
-fn disassemble_ipl3_withhead(path: &str) -> Disassembly {
-    let bytes = std::fs::read(Path::new(path)).unwrap();
+#[allow(dead_code)]
+fn disassemble_disk_ipl(path: &str) -> Result<Disassembly, Box<dyn Error>> {
+    let disk = DiskImage::open(path)?;
+    let id = disk.disk_id();
+    println!("{}: {} v{} disk {}, type {}", path, String::from_utf8_lossy(&id.game_code), id.version, id.disk_number, disk.disk_type());
     
-    Disassembly::from_u8(&Vec::from(bytes.split_at(0x1000).0))
+    Ok(disk.ipl_disassembly())
 }
 
-fn disassemble_pifrom(path: &str) -> Disassembly {
-    let bytes = std::fs::read(Path::new(path)).unwrap();
+#[allow(dead_code)]
+fn disassemble_ddipl(path: &str) -> Result<Disassembly, Box<dyn Error>> {
+    let (rom, _) = Rom::lenient(std::fs::read(path)?);
     
-    Disassembly::from_u8(&bytes)
+    Ok(rom.ipl_rom_disassembly()?)
 }
 
-fn save_disassembly(disasm: Disassembly, path: &str) {
-    let mut out = File::create(path).unwrap();
-    for (i, instr) in disasm.instructions.iter().enumerate() {
-        out.write_all(format!("[{:#010X}]{}\n", (i * 4) + 0x00, instr).as_bytes()).unwrap();
-    }
+fn disassemble_pifrom(path: &str) -> std::io::Result<Disassembly> {
+    let bytes = std::fs::read(Path::new(path))?;
+    
+    Ok(Disassembly::from_rom(&bytes, 0, bytes.len(), 0xBFC00000, Target::VR4300))
+}
+
+fn save_disassembly(disasm: Disassembly, path: &str) -> std::io::Result<()> {
+    let mut out = File::create(path)?;
+    out.write_all(disasm.render(&Style::compact()).as_bytes())
 }
\ No newline at end of file
@@ -0,0 +1,281 @@
+use crate::disassembly::{format_args, Disassembly, Instruction, Operand, Operation, RegNames};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq, strum_macros::Display, Copy, Clone)]
+pub enum PseudoOp {
+    B,
+    BAL,
+    BEQZ,
+    BEQZL,
+    BNEZ,
+    BNEZL,
+    
+    LA,
+    LI,
+    MOVE,
+    
+    NEG,
+    NEGU,
+    NOT,
+    
+    NOP,
+}
+
+/// An alias for one or more consecutive real instructions.
+#[derive(PartialEq, Clone)]
+pub struct PseudoInstruction<'a> {
+    pub op: PseudoOp,
+    pub args: [Option<Operand>; 4],
+    /// Index of the first underlying instruction in the source [`Disassembly`].
+    pub index: usize,
+    pub instructions: &'a [Instruction],
+}
+
+impl<'a> PseudoInstruction<'a> {
+    /// Displays this pseudo-instruction using the given register names.
+    pub fn display(&self, names: RegNames) -> PseudoDisplay<'_, 'a> {
+        PseudoDisplay { pseudo: self, names }
+    }
+}
+
+pub struct PseudoDisplay<'b, 'a> {
+    pseudo: &'b PseudoInstruction<'a>,
+    names: RegNames,
+}
+
+impl<'b, 'a> Display for PseudoDisplay<'b, 'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let args = format_args(&self.pseudo.args, self.names);
+        
+        write!(f, "[{:#010X}][{} {}]", self.pseudo.instructions[0].code, self.pseudo.op, args)
+    }
+}
+
+impl<'a> Display for PseudoInstruction<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display(RegNames::default()))
+    }
+}
+
+#[derive(PartialEq, Clone)]
+pub enum Item<'a> {
+    Real(usize, &'a Instruction),
+    Pseudo(PseudoInstruction<'a>),
+}
+
+impl<'a> Item<'a> {
+    /// Index of the first instruction covered by this item.
+    pub fn index(&self) -> usize {
+        match self {
+            Item::Real(index, _) => *index,
+            Item::Pseudo(pseudo) => pseudo.index,
+        }
+    }
+    
+    /// The real instructions this item was made from.
+    pub fn instructions(&self) -> &'a [Instruction] {
+        match self {
+            Item::Real(_, instr) => std::slice::from_ref(*instr),
+            Item::Pseudo(pseudo) => pseudo.instructions,
+        }
+    }
+}
+
+impl<'a> Item<'a> {
+    /// Displays this item using the given register names.
+    pub fn display(&self, names: RegNames) -> ItemDisplay<'_, 'a> {
+        ItemDisplay { item: self, names }
+    }
+}
+
+pub struct ItemDisplay<'b, 'a> {
+    item: &'b Item<'a>,
+    names: RegNames,
+}
+
+impl<'b, 'a> Display for ItemDisplay<'b, 'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.item {
+            Item::Real(_, instr) => write!(f, "{}", instr.display(self.names)),
+            Item::Pseudo(pseudo) => write!(f, "{}", pseudo.display(self.names)),
+        }
+    }
+}
+
+impl<'a> Display for Item<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display(RegNames::default()))
+    }
+}
+
+const ZR: Operand = Operand::Reg(0);
+
+/// Recognizes aliases that stand in for a single instruction.
+fn single(instr: &Instruction) -> Option<(PseudoOp, [Option<Operand>; 4])> {
+    use self::{Operation::*, PseudoOp::*};
+    
+    let args = instr.args;
+    
+    match (instr.op, args) {
+        (SLL, [Some(ZR), ..]) | (SRL, [Some(ZR), ..]) | (SRA, [Some(ZR), ..]) => Some((PseudoOp::NOP, [None; 4])),
+        
+        (ADDU, [Some(rd), Some(rs), Some(ZR), _]) | (OR, [Some(rd), Some(rs), Some(ZR), _]) | (DADDU, [Some(rd), Some(rs), Some(ZR), _]) |
+        (ADDU, [Some(rd), Some(ZR), Some(rs), _]) | (OR, [Some(rd), Some(ZR), Some(rs), _]) | (DADDU, [Some(rd), Some(ZR), Some(rs), _]) => Some((MOVE, [Some(rd), Some(rs), None, None])),
+        
+        (NOR, [Some(rd), Some(rs), Some(ZR), _]) | (NOR, [Some(rd), Some(ZR), Some(rs), _]) => Some((NOT, [Some(rd), Some(rs), None, None])),
+        (SUB, [Some(rd), Some(ZR), Some(rt), _]) => Some((PseudoOp::NEG, [Some(rd), Some(rt), None, None])),
+        (SUBU, [Some(rd), Some(ZR), Some(rt), _]) => Some((NEGU, [Some(rd), Some(rt), None, None])),
+        
+        (ADDIU, [Some(rt), Some(ZR), Some(imm), _]) | (ORI, [Some(rt), Some(ZR), Some(imm), _]) => Some((LI, [Some(rt), Some(imm), None, None])),
+        
+        (BEQ, [Some(ZR), Some(ZR), Some(target), _]) | (BGEZ, [Some(ZR), Some(target), ..]) => Some((B, [Some(target), None, None, None])),
+        (BGEZAL, [Some(ZR), Some(target), ..]) => Some((BAL, [Some(target), None, None, None])),
+        
+        (BEQ, [Some(rs), Some(ZR), Some(target), _]) | (BEQ, [Some(ZR), Some(rs), Some(target), _]) => Some((BEQZ, [Some(rs), Some(target), None, None])),
+        (BNE, [Some(rs), Some(ZR), Some(target), _]) | (BNE, [Some(ZR), Some(rs), Some(target), _]) => Some((BNEZ, [Some(rs), Some(target), None, None])),
+        (BEQL, [Some(rs), Some(ZR), Some(target), _]) | (BEQL, [Some(ZR), Some(rs), Some(target), _]) => Some((BEQZL, [Some(rs), Some(target), None, None])),
+        (BNEL, [Some(rs), Some(ZR), Some(target), _]) | (BNEL, [Some(ZR), Some(rs), Some(target), _]) => Some((BNEZL, [Some(rs), Some(target), None, None])),
+        
+        _ => None
+    }
+}
+
+/// Recognizes the `lui` + `ori`/`addiu` idioms used to build 32-bit constants and addresses.
+fn pair(first: &Instruction, second: &Instruction) -> Option<(PseudoOp, [Option<Operand>; 4])> {
+    use self::{Operand::*, Operation::*, PseudoOp::*};
+    
+    let (rt, upper) = match (first.op, first.args) {
+        (LUI, [Some(Reg(rt)), Some(Lit16(upper)), ..]) if rt != 0 => (rt, (upper as u32) << 16),
+        _ => return None
+    };
+    
+    match (second.op, second.args) {
+        (ORI, [Some(Reg(dst)), Some(Reg(src)), Some(Lit16(lower)), _]) if dst == rt && src == rt => {
+            Some((LI, [Some(Reg(rt)), Some(Lit32(upper | lower as u32)), None, None]))
+        },
+        (ADDIU, [Some(Reg(dst)), Some(Reg(src)), Some(Imm16(lower)), _]) if dst == rt && src == rt => {
+            Some((LA, [Some(Reg(rt)), Some(Lit32(upper.wrapping_add(lower as i32 as u32))), None, None]))
+        },
+        _ => None
+    }
+}
+
+/// Runs the pseudo-instruction pass over a disassembly.
+///
+/// Instructions that have no alias are kept as [`Item::Real`]. Pairs are never merged across a delay slot, since
+/// the second instruction would then not always execute after the first.
+pub fn recognize(disasm: &Disassembly) -> Vec<Item<'_>> {
+    let instrs = &disasm.instructions;
+    let mut items = Vec::new();
+    
+    let mut i = 0;
+    while i < instrs.len() {
+        let in_delay_slot = i > 0 && instrs[i - 1].has_delay_slot();
+        
+        if let (false, Some(next)) = (in_delay_slot, instrs.get(i + 1)) {
+            if let Some((op, args)) = pair(&instrs[i], next) {
+                items.push(Item::Pseudo(PseudoInstruction { op, args, index: i, instructions: &instrs[i..(i + 2)] }));
+                i += 2;
+                continue;
+            }
+        }
+        
+        match single(&instrs[i]) {
+            Some((op, args)) => items.push(Item::Pseudo(PseudoInstruction { op, args, index: i, instructions: &instrs[i..(i + 1)] })),
+            None => items.push(Item::Real(i, &instrs[i])),
+        }
+        i += 1;
+    }
+    
+    items
+}
+
+impl Disassembly {
+    /// Convenience wrapper around [`recognize`].
+    pub fn pseudo(&self) -> Vec<Item<'_>> {
+        recognize(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recognize, Item, PseudoOp, PseudoOp::*};
+    use crate::disassembly::{Disassembly, Operand, Operand::*, Operation};
+    
+    /// The op and args of every item, with real instructions given as `None`.
+    fn ops(disasm: &Disassembly) -> Vec<Option<(PseudoOp, Vec<Operand>)>> {
+        recognize(disasm).iter().map(|item| match item {
+            Item::Pseudo(pseudo) => Some((pseudo.op, pseudo.args.iter().flatten().copied().collect())),
+            Item::Real(..) => None,
+        }).collect()
+    }
+    
+    #[test]
+    fn single_instruction_aliases() {
+        for (code, op, args) in &[
+            (0x00801021, MOVE, vec![Reg(2), Reg(4)]), // addu v0, a0, zr
+            (0x00041025, MOVE, vec![Reg(2), Reg(4)]), // or v0, zr, a0
+            (0x0080102D, MOVE, vec![Reg(2), Reg(4)]), // daddu v0, a0, zr
+            (0x00801027, NOT, vec![Reg(2), Reg(4)]), // nor v0, a0, zr
+            (0x00041022, NEG, vec![Reg(2), Reg(4)]), // sub v0, zr, a0
+            (0x00041023, NEGU, vec![Reg(2), Reg(4)]), // subu v0, zr, a0
+            (0x24020005, LI, vec![Reg(2), Imm16(5)]), // addiu v0, zr, 5
+            (0x34028000, LI, vec![Reg(2), Lit16(0x8000)]), // ori v0, zr, 0x8000
+            (0x10000003, B, vec![Branch(3)]), // beq zr, zr
+            (0x04010003, B, vec![Branch(3)]), // bgez zr
+            (0x04110003, BAL, vec![Branch(3)]), // bgezal zr
+            (0x10800003, BEQZ, vec![Reg(4), Branch(3)]), // beq a0, zr
+            (0x10040003, BEQZ, vec![Reg(4), Branch(3)]), // beq zr, a0
+            (0x14800003, BNEZ, vec![Reg(4), Branch(3)]), // bne a0, zr
+            (0x50800003, BEQZL, vec![Reg(4), Branch(3)]), // beql a0, zr
+            (0x54800003, BNEZL, vec![Reg(4), Branch(3)]), // bnel a0, zr
+            (0x00080100, NOP, vec![]), // sll zr, t0, 4
+            (0x00000002, NOP, vec![]), // srl zr, zr, 0
+            (0x00000003, NOP, vec![]), // sra zr, zr, 0
+        ] {
+            assert_eq!(ops(&Disassembly::from_u32(&[*code])), vec![Some((*op, args.clone()))], "{:#010X}", code);
+        }
+        
+        // Already a NOP, and instructions that only look like aliases
+        for code in &[0x00000000, 0x00851021, 0x00A41022, 0x14850003, 0x24820005] {
+            assert_eq!(ops(&Disassembly::from_u32(&[*code])), vec![None], "{:#010X}", code);
+        }
+    }
+    
+    #[test]
+    fn constant_pairs() {
+        // lui t0, 0x1234; ori t0, t0, 0x5678
+        assert_eq!(ops(&Disassembly::from_u32(&[0x3C081234, 0x35085678])), vec![Some((LI, vec![Reg(8), Lit32(0x12345678)]))]);
+        // lui t0, 0x8001; addiu t0, t0, -0x8000 borrows from the upper half
+        assert_eq!(ops(&Disassembly::from_u32(&[0x3C088001, 0x25088000])), vec![Some((LA, vec![Reg(8), Lit32(0x80008000)]))]);
+        // lui t0, 0x1234; ori t1, t0, 0x5678 builds the constant in another register
+        assert_eq!(ops(&Disassembly::from_u32(&[0x3C081234, 0x35095678])), vec![None, None]);
+        // lui zr is never paired
+        assert_eq!(ops(&Disassembly::from_u32(&[0x3C001234, 0x35085678])), vec![None, None]);
+    }
+    
+    #[test]
+    fn delay_slots() {
+        // bne a0, a1; lui t0, 0x1234 (delay slot); ori t0, t0, 0x5678
+        let disasm = Disassembly::from_u32(&[0x14850003, 0x3C081234, 0x35085678]);
+        assert_eq!(ops(&disasm), vec![None, None, None]);
+        
+        // The same pair right after the delay slot is merged
+        let disasm = Disassembly::from_u32(&[0x14850003, 0x00000000, 0x3C081234, 0x35085678]);
+        assert_eq!(ops(&disasm), vec![None, None, Some((LI, vec![Reg(8), Lit32(0x12345678)]))]);
+    }
+    
+    #[test]
+    fn underlying_instructions() {
+        let disasm = Disassembly::from_u32(&[0x00801021, 0x3C088001, 0x25088000, 0x14850003]);
+        let items = disasm.pseudo();
+        
+        assert_eq!(items.iter().map(|item| item.index()).collect::<Vec<usize>>(), vec![0, 1, 3]);
+        assert_eq!(items[0].instructions().iter().map(|instr| instr.op).collect::<Vec<Operation>>(), vec![Operation::ADDU]);
+        assert_eq!(items[1].instructions().iter().map(|instr| instr.code).collect::<Vec<u32>>(), vec![0x3C088001, 0x25088000]);
+        assert_eq!(items[2].instructions()[0].op, Operation::BNE);
+        assert!(matches!(items[2], Item::Real(3, _)));
+        
+        assert_eq!(items[1].to_string(), "[0x3C088001][LA t0, 0x80008000]");
+    }
+}
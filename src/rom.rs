@@ -1,6 +1,71 @@
+use encoding_rs::SHIFT_JIS;
 use std::convert::TryInto;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
 
-#[derive(Debug)]
+/// Byte order of a ROM dump. The cartridge itself is big-endian; the others come from old copier devices.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ByteOrder {
+    /// `.z64`, as read from the cartridge.
+    BigEndian,
+    /// `.v64`, with the bytes of every 16-bit half swapped.
+    ByteSwapped,
+    /// `.n64`, with every 32-bit word reversed.
+    LittleEndian,
+}
+
+impl ByteOrder {
+    /// First word of a cartridge header (the PI BSD DOM1 register values), as it appears in a big-endian dump.
+    pub const MAGIC: u32 = 0x80371240;
+    
+    /// Detects the byte order from the first word of a dump.
+    pub fn detect(bytes: &[u8]) -> Option<ByteOrder> {
+        match bytes.get(0..4)? {
+            [0x80, 0x37, 0x12, 0x40] => Some(ByteOrder::BigEndian),
+            [0x37, 0x80, 0x40, 0x12] => Some(ByteOrder::ByteSwapped),
+            [0x40, 0x12, 0x37, 0x80] => Some(ByteOrder::LittleEndian),
+            _ => None
+        }
+    }
+    
+    pub fn from_extension(extension: &str) -> Option<ByteOrder> {
+        match extension.to_ascii_lowercase().as_str() {
+            "z64" => Some(ByteOrder::BigEndian),
+            "v64" => Some(ByteOrder::ByteSwapped),
+            "n64" => Some(ByteOrder::LittleEndian),
+            _ => None
+        }
+    }
+    
+    pub fn extension(self) -> &'static str {
+        match self {
+            ByteOrder::BigEndian => "z64",
+            ByteOrder::ByteSwapped => "v64",
+            ByteOrder::LittleEndian => "n64",
+        }
+    }
+    
+    /// Converts `bytes` between this order and big-endian, in place. Every conversion is its own inverse, so this works in
+    /// either direction. A trailing partial half or word is left as is.
+    pub fn swap(self, bytes: &mut [u8]) {
+        match self {
+            ByteOrder::BigEndian => (),
+            ByteOrder::ByteSwapped => bytes.chunks_exact_mut(2).for_each(|half| half.swap(0, 1)),
+            ByteOrder::LittleEndian => bytes.chunks_exact_mut(4).for_each(|word| word.reverse()),
+        }
+    }
+    
+    /// Converts `bytes` from this order to `to`.
+    pub fn convert(self, bytes: &mut [u8], to: ByteOrder) {
+        self.swap(bytes);
+        to.swap(bytes);
+    }
+}
+
+/// Size of the cartridge header.
+pub const HEADER_SIZE: usize = 0x40;
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Header {
     pub pi_regs: u32,
     pub clockrate: u32,
@@ -16,6 +81,107 @@ pub struct Header {
     pub country: u16,
 }
 
+/// Why a header edit was rejected.
+#[derive(Debug, PartialEq, Clone)]
+pub enum HeaderError {
+    /// The name doesn't fit in the 20 bytes of `image_name`.
+    NameTooLong(usize),
+    /// The name has characters that Shift-JIS can't represent.
+    Unencodable(String),
+    /// Game codes are exactly 4 ASCII characters.
+    InvalidGameCode(String),
+}
+
+impl Display for HeaderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeaderError::NameTooLong(len) => write!(f, "name is {} bytes long, the limit is 20", len),
+            HeaderError::Unencodable(name) => write!(f, "name `{}` can't be encoded as Shift-JIS", name),
+            HeaderError::InvalidGameCode(code) => write!(f, "game code `{}` is not 4 ASCII characters", code),
+        }
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+impl Header {
+    /// `image_name` decoded as Shift-JIS, without the trailing padding. Most names are plain ASCII, but Japanese ones
+    /// often use half-width katakana.
+    pub fn name(&self) -> String {
+        let (name, _, _) = SHIFT_JIS.decode(&self.image_name);
+        
+        name.trim_end_matches([' ', '\0']).to_string()
+    }
+    
+    /// Sets `image_name`, encoded as Shift-JIS and padded with spaces.
+    pub fn set_name(&mut self, name: &str) -> Result<(), HeaderError> {
+        let (bytes, _, unmappable) = SHIFT_JIS.encode(name);
+        if unmappable {
+            return Err(HeaderError::Unencodable(name.to_string()));
+        }
+        
+        self.set_name_bytes(&bytes)
+    }
+    
+    /// Sets `image_name` from already encoded bytes, padding it with spaces.
+    pub fn set_name_bytes(&mut self, name: &[u8]) -> Result<(), HeaderError> {
+        if name.len() > self.image_name.len() {
+            return Err(HeaderError::NameTooLong(name.len()));
+        }
+        
+        self.image_name = [b' '; 20];
+        self.image_name[..name.len()].copy_from_slice(name);
+        Ok(())
+    }
+    
+    /// The 4 character code at 0x3B, made of the media type, 2 character game ID and region (e.g. `NSME`).
+    pub fn game_code(&self) -> [u8; 4] {
+        let cart_id = self.cart_id.to_be_bytes();
+        
+        [self.manu_id as u8, cart_id[0], cart_id[1], (self.country >> 8) as u8]
+    }
+    
+    pub fn set_game_code(&mut self, code: &str) -> Result<(), HeaderError> {
+        let bytes = code.as_bytes();
+        if bytes.len() != 4 || !code.is_ascii() {
+            return Err(HeaderError::InvalidGameCode(code.to_string()));
+        }
+        
+        self.manu_id = (self.manu_id & 0xFFFFFF00) | bytes[0] as u32;
+        self.cart_id = to_u16(&bytes[1..3]);
+        self.country = ((bytes[3] as u16) << 8) | (self.country & 0x00FF);
+        Ok(())
+    }
+    
+    /// The version byte at 0x3F.
+    pub fn version(&self) -> u8 {
+        self.country as u8
+    }
+    
+    pub fn set_version(&mut self, version: u8) {
+        self.country = (self.country & 0xFF00) | version as u16;
+    }
+}
+
+impl From<&[u8; HEADER_SIZE]> for Header {
+    fn from(bytes: &[u8; HEADER_SIZE]) -> Header {
+        Header {
+            pi_regs: to_u32(&bytes[0..4]),
+            clockrate: to_u32(&bytes[4..8]),
+            pc: to_u32(&bytes[8..12]),
+            release: to_u32(&bytes[12..16]),
+            crc1: to_u32(&bytes[16..20]),
+            crc2: to_u32(&bytes[20..24]),
+            unknown0: to_u64(&bytes[24..32]),
+            image_name: bytes[32..52].try_into().unwrap(),
+            unknown1: to_u32(&bytes[52..56]),
+            manu_id: to_u32(&bytes[56..60]),
+            cart_id: to_u16(&bytes[60..62]),
+            country: to_u16(&bytes[62..64]),
+        }
+    }
+}
+
 trait ByteUtil2 { fn to_u8_tuple(&self) -> (u8, u8); }
 impl ByteUtil2 for u16 {
     fn to_u8_tuple(&self) -> (u8, u8) {
@@ -40,20 +206,26 @@ impl ByteUtil8 for u64 {
     }
 }
 
-impl Into<[u8; 0x40]> for Header {
-    fn into(self) -> [u8; 0x40] {
-        let pi_regs = self.pi_regs.to_u8_tuple();
-        let clockrate = self.clockrate.to_u8_tuple();
-        let pc = self.pc.to_u8_tuple();
-        let release = self.release.to_u8_tuple();
-        let crc1 = self.crc1.to_u8_tuple();
-        let crc2 = self.crc2.to_u8_tuple();
-        let unknown0 = self.unknown0.to_u8_tuple();
-        let img = self.image_name;
-        let unknown1 = self.unknown1.to_u8_tuple();
-        let manu_id = self.manu_id.to_u8_tuple();
-        let cart_id = self.cart_id.to_u8_tuple();
-        let country = self.country.to_u8_tuple();
+impl From<Header> for [u8; HEADER_SIZE] {
+    fn from(header: Header) -> [u8; HEADER_SIZE] {
+        (&header).into()
+    }
+}
+
+impl From<&Header> for [u8; HEADER_SIZE] {
+    fn from(header: &Header) -> [u8; HEADER_SIZE] {
+        let pi_regs = header.pi_regs.to_u8_tuple();
+        let clockrate = header.clockrate.to_u8_tuple();
+        let pc = header.pc.to_u8_tuple();
+        let release = header.release.to_u8_tuple();
+        let crc1 = header.crc1.to_u8_tuple();
+        let crc2 = header.crc2.to_u8_tuple();
+        let unknown0 = header.unknown0.to_u8_tuple();
+        let img = header.image_name;
+        let unknown1 = header.unknown1.to_u8_tuple();
+        let manu_id = header.manu_id.to_u8_tuple();
+        let cart_id = header.cart_id.to_u8_tuple();
+        let country = header.country.to_u8_tuple();
         
         [
             pi_regs.0, pi_regs.1, pi_regs.2, pi_regs.3,
@@ -72,56 +244,439 @@ impl Into<[u8; 0x40]> for Header {
     }
 }
 
+/// Size of the header and bootcode, which every ROM starts with.
+pub const MIN_SIZE: usize = 0x1000;
+
+/// Why a dump was rejected as a ROM.
 #[derive(Debug)]
+pub enum RomError {
+    Io(std::io::Error),
+    /// Smaller than the header and bootcode.
+    TooShort(usize),
+    /// The size isn't a multiple of 4 bytes.
+    UnalignedSize(usize),
+    /// The first word isn't the header magic in any of the known byte orders.
+    UnknownByteOrder(u32),
+    /// The first word isn't the header magic after converting from the given byte order.
+    BadMagic(u32),
+}
+
+impl Display for RomError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use RomError::*;
+        
+        match self {
+            Io(err) => write!(f, "{}", err),
+            TooShort(len) => write!(f, "{:#X} bytes is too short for a ROM, at least {:#X} are needed", len, MIN_SIZE),
+            UnalignedSize(len) => write!(f, "size {:#X} is not a multiple of 4 bytes", len),
+            UnknownByteOrder(word) => write!(f, "unknown byte order, first word is {:#010X}", word),
+            BadMagic(word) => write!(f, "bad header magic {:#010X}, expected {:#010X}", word, ByteOrder::MAGIC),
+        }
+    }
+}
+
+impl std::error::Error for RomError {}
+
+impl From<std::io::Error> for RomError {
+    fn from(err: std::io::Error) -> Self {
+        RomError::Io(err)
+    }
+}
+
+#[derive(Debug)]
+/// A ROM image, along with its parsed header and bootcode.
+/// 
+/// The image is the single source of truth: the header and bootcode can only be changed through methods that also
+/// write them back into it, so all three always agree.
 pub struct Rom {
-    pub header: Header,
-    pub bootcode: [u32; 1008],
-    pub data: Vec<u8>,
+    header: Header,
+    bootcode: [u32; 1008],
+    /// The whole ROM, always big-endian.
+    data: Vec<u8>,
+    /// Byte order of the dump this was loaded from.
+    pub byte_order: ByteOrder,
 }
 
 impl Rom {
-    pub fn new(bytes: Vec<u8>) -> Rom {
-        let header = Header {
-            pi_regs: to_u32(&bytes[0..4]),
-            clockrate: to_u32(&bytes[4..8]),
-            pc: to_u32(&bytes[8..12]),
-            release: to_u32(&bytes[12..16]),
-            crc1: to_u32(&bytes[16..20]),
-            crc2: to_u32(&bytes[20..24]),
-            unknown0: to_u64(&bytes[24..32]),
-            image_name: bytes[32..52].try_into().unwrap(),
-            unknown1: to_u32(&bytes[52..56]),
-            manu_id: to_u32(&bytes[56..60]),
-            cart_id: to_u16(&bytes[60..62]),
-            country: to_u16(&bytes[62..64]),
-        };
+    /// Parses a dump in any byte order, normalizing it to big-endian.
+    pub fn new(bytes: Vec<u8>) -> Result<Rom, RomError> {
+        if bytes.len() < MIN_SIZE {
+            return Err(RomError::TooShort(bytes.len()));
+        }
         
-        let mut bootcode = [0u32; 1008];
-        for i in 0..bootcode.len() {
-            bootcode[i] = to_u32(&[
-                bytes[64 + (i * 4) + 0],
-                bytes[64 + (i * 4) + 1],
-                bytes[64 + (i * 4) + 2],
-                bytes[64 + (i * 4) + 3]
-            ]).try_into().unwrap();
+        match ByteOrder::detect(&bytes) {
+            Some(order) => Rom::with_byte_order(bytes, order),
+            None => Err(RomError::UnknownByteOrder(to_u32(&bytes[0..4]))),
+        }
+    }
+    
+    /// Parses a dump known to be in `order` (e.g. going by its file extension), normalizing it to big-endian.
+    pub fn with_byte_order(mut bytes: Vec<u8>, order: ByteOrder) -> Result<Rom, RomError> {
+        if bytes.len() < MIN_SIZE {
+            return Err(RomError::TooShort(bytes.len()));
+        }
+        if !bytes.len().is_multiple_of(4) {
+            return Err(RomError::UnalignedSize(bytes.len()));
+        }
+        
+        order.swap(&mut bytes);
+        match to_u32(&bytes[0..4]) {
+            ByteOrder::MAGIC => Ok(Rom::parse(bytes, order)),
+            word => Err(RomError::BadMagic(word)),
+        }
+    }
+    
+    /// Parses what it can from truncated, headerless or otherwise damaged dumps. Dumps in an unknown byte order are
+    /// assumed to be big-endian, and anything missing from the header or bootcode reads as zero.
+    /// 
+    /// Also returns the problems that [`Rom::new`] would have rejected the dump for.
+    pub fn lenient(mut bytes: Vec<u8>) -> (Rom, Vec<RomError>) {
+        let mut problems = Vec::new();
+        
+        if bytes.len() < MIN_SIZE {
+            problems.push(RomError::TooShort(bytes.len()));
+        }
+        if !bytes.len().is_multiple_of(4) {
+            problems.push(RomError::UnalignedSize(bytes.len()));
         }
         
+        let order = match ByteOrder::detect(&bytes) {
+            Some(order) => order,
+            None => {
+                if bytes.len() >= 4 {
+                    problems.push(RomError::UnknownByteOrder(to_u32(&bytes[0..4])));
+                }
+                ByteOrder::BigEndian
+            }
+        };
+        order.swap(&mut bytes);
+        
+        (Rom::parse(bytes, order), problems)
+    }
+    
+    fn parse(bytes: Vec<u8>, byte_order: ByteOrder) -> Rom {
+        let mut head = [0u8; MIN_SIZE];
+        let len = bytes.len().min(MIN_SIZE);
+        head[..len].copy_from_slice(&bytes[..len]);
+        
         Rom {
-            header: header,
-            bootcode: bootcode,
+            header: Header::from(&head[..HEADER_SIZE].try_into().unwrap()),
+            bootcode: parse_bootcode(&head),
             data: bytes,
+            byte_order,
+        }
+    }
+    
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Rom, RomError> {
+        Rom::new(std::fs::read(path)?)
+    }
+    
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+    
+    pub fn bootcode(&self) -> &[u32; 1008] {
+        &self.bootcode
+    }
+    
+    /// The whole ROM, big-endian.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+    
+    /// Replaces the header, writing it into the image. An image shorter than the header is first padded with zeros.
+    pub fn set_header(&mut self, header: Header) {
+        let bytes: [u8; HEADER_SIZE] = (&header).into();
+        self.write(0, &bytes);
+    }
+    
+    /// Edits the header in place, then writes it into the image, e.g.
+    /// `rom.edit_header(|header| header.set_game_code("NSME"))`.
+    pub fn edit_header<R, F: FnOnce(&mut Header) -> R>(&mut self, edit: F) -> R {
+        let mut header = self.header.clone();
+        let result = edit(&mut header);
+        self.set_header(header);
+        
+        result
+    }
+    
+    pub fn set_name(&mut self, name: &str) -> Result<(), HeaderError> {
+        self.edit_header(|header| header.set_name(name))
+    }
+    
+    pub fn set_game_code(&mut self, code: &str) -> Result<(), HeaderError> {
+        self.edit_header(|header| header.set_game_code(code))
+    }
+    
+    pub fn set_version(&mut self, version: u8) {
+        self.edit_header(|header| header.set_version(version))
+    }
+    
+    pub fn set_clockrate(&mut self, clockrate: u32) {
+        self.edit_header(|header| header.clockrate = clockrate)
+    }
+    
+    pub fn set_entry_point(&mut self, pc: u32) {
+        self.edit_header(|header| header.pc = pc)
+    }
+    
+    /// Replaces the bootcode, writing it into the image. An image shorter than [`MIN_SIZE`] is first padded with zeros.
+    pub fn set_bootcode(&mut self, bootcode: &[u32; 1008]) {
+        let bytes: Vec<u8> = bootcode.iter().flat_map(|word| word.to_be_bytes()).collect();
+        self.write(HEADER_SIZE, &bytes);
+    }
+    
+    /// Overwrites the image at `offset`, padding it with zeros if it's too short, and reparses the header and bootcode
+    /// if they were touched.
+    pub fn write(&mut self, offset: usize, bytes: &[u8]) {
+        let end = offset + bytes.len();
+        if self.data.len() < end {
+            self.data.resize(end, 0);
         }
+        self.data[offset..end].copy_from_slice(bytes);
+        
+        if offset < MIN_SIZE {
+            let mut head = [0u8; MIN_SIZE];
+            let len = self.data.len().min(MIN_SIZE);
+            head[..len].copy_from_slice(&self.data[..len]);
+            
+            self.header = Header::from(&head[..HEADER_SIZE].try_into().unwrap());
+            self.bootcode = parse_bootcode(&head);
+        }
+    }
+    
+    /// The ROM converted to the given byte order.
+    pub fn to_bytes(&self, order: ByteOrder) -> Vec<u8> {
+        let mut bytes = self.data.clone();
+        order.swap(&mut bytes);
+        
+        bytes
+    }
+    
+    /// Writes the ROM in the given byte order, e.g. `rom.save("out.z64", ByteOrder::BigEndian)`.
+    pub fn save<P: AsRef<Path>>(&self, path: P, order: ByteOrder) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes(order))
+    }
+}
+
+fn parse_bootcode(head: &[u8; MIN_SIZE]) -> [u32; 1008] {
+    let mut bootcode = [0u32; 1008];
+    for (i, word) in bootcode.iter_mut().enumerate() {
+        *word = to_u32(&head[(HEADER_SIZE + (i * 4))..(HEADER_SIZE + (i * 4) + 4)]);
     }
+    
+    bootcode
 }
 
-fn to_u16(bytes: &[u8]) -> u16 {
+pub(crate) fn to_u16(bytes: &[u8]) -> u16 {
     ((bytes[0] as u16) << 8) | (bytes[1] as u16)
 }
 
-fn to_u32(bytes: &[u8]) -> u32 {
+pub(crate) fn to_u32(bytes: &[u8]) -> u32 {
     ((bytes[0] as u32) << 24) | ((bytes[1] as u32) << 16) | ((bytes[2] as u32) << 8) | (bytes[3] as u32)
 }
 
 fn to_u64(bytes: &[u8]) -> u64 {
     ((to_u32(&bytes[0..4]) as u64) << 32) | (to_u32(&bytes[4..8]) as u64)
-}
\ No newline at end of file
+}
+
+/// Big-endian ROM images for tests, zero filled apart from the magic word.
+#[cfg(test)]
+pub(crate) struct TestRom {
+    bytes: Vec<u8>,
+}
+
+#[cfg(test)]
+pub(crate) fn test_rom(len: usize) -> TestRom {
+    TestRom { bytes: vec![0u8; len] }.with_code(0, &[ByteOrder::MAGIC])
+}
+
+#[cfg(test)]
+impl TestRom {
+    /// Fills every byte from its offset, then puts the magic word back.
+    pub(crate) fn with_fill<F: Fn(u32) -> u8>(mut self, fill: F) -> TestRom {
+        for (i, byte) in self.bytes.iter_mut().enumerate() {
+            *byte = fill(i as u32);
+        }
+        
+        self.with_code(0, &[ByteOrder::MAGIC])
+    }
+    
+    pub(crate) fn with_pc(self, pc: u32) -> TestRom {
+        self.with_code(8, &[pc])
+    }
+    
+    pub(crate) fn with_bytes(mut self, offset: usize, bytes: &[u8]) -> TestRom {
+        self.bytes[offset..(offset + bytes.len())].copy_from_slice(bytes);
+        self
+    }
+    
+    pub(crate) fn with_code(self, offset: usize, code: &[u32]) -> TestRom {
+        let bytes: Vec<u8> = code.iter().flat_map(|word| word.to_be_bytes()).collect();
+        self.with_bytes(offset, &bytes)
+    }
+    
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+    
+    pub(crate) fn rom(self) -> Rom {
+        Rom::new(self.bytes).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{test_rom, ByteOrder, Header, HeaderError, Rom, RomError, HEADER_SIZE};
+    
+    fn z64() -> Vec<u8> {
+        test_rom(0x1000).with_fill(|i| (i * 7) as u8).with_pc(0x80000400).into_bytes()
+    }
+    
+    #[test]
+    fn detection() {
+        assert_eq!(ByteOrder::detect(&[0x80, 0x37, 0x12, 0x40]), Some(ByteOrder::BigEndian));
+        assert_eq!(ByteOrder::detect(&[0x37, 0x80, 0x40, 0x12]), Some(ByteOrder::ByteSwapped));
+        assert_eq!(ByteOrder::detect(&[0x40, 0x12, 0x37, 0x80]), Some(ByteOrder::LittleEndian));
+        assert_eq!(ByteOrder::detect(&[0x80, 0x37, 0x12, 0x41]), None);
+        assert_eq!(ByteOrder::detect(&[0x80, 0x37]), None);
+        
+        for order in &[ByteOrder::BigEndian, ByteOrder::ByteSwapped, ByteOrder::LittleEndian] {
+            assert_eq!(ByteOrder::from_extension(order.extension()), Some(*order));
+        }
+    }
+    
+    #[test]
+    fn normalizes_and_converts() {
+        let orders = [ByteOrder::BigEndian, ByteOrder::ByteSwapped, ByteOrder::LittleEndian];
+        
+        for from in &orders {
+            let mut dump = z64();
+            from.swap(&mut dump);
+            assert_eq!(ByteOrder::detect(&dump), Some(*from));
+            
+            let rom = Rom::new(dump.clone()).unwrap();
+            assert_eq!(rom.byte_order, *from);
+            assert_eq!(rom.data, z64());
+            assert_eq!(rom.header.pc, 0x80000400);
+            assert_eq!(rom.to_bytes(*from), dump);
+            
+            for to in &orders {
+                let mut converted = dump.clone();
+                from.convert(&mut converted, *to);
+                
+                assert_eq!(rom.to_bytes(*to), converted);
+                assert_eq!(ByteOrder::detect(&converted), Some(*to));
+            }
+        }
+    }
+    
+    #[test]
+    fn errors() {
+        let mut bytes = z64();
+        
+        assert!(matches!(Rom::new(bytes[..0xFFC].to_vec()), Err(RomError::TooShort(0xFFC))));
+        assert!(matches!(Rom::new(Vec::new()), Err(RomError::TooShort(0))));
+        
+        bytes.push(0);
+        assert!(matches!(Rom::new(bytes.clone()), Err(RomError::UnalignedSize(0x1001))));
+        bytes.pop();
+        
+        assert!(matches!(Rom::with_byte_order(bytes.clone(), ByteOrder::ByteSwapped), Err(RomError::BadMagic(0x37804012))));
+        
+        bytes[0] = 0x00;
+        assert!(matches!(Rom::new(bytes.clone()), Err(RomError::UnknownByteOrder(0x00371240))));
+        assert!(matches!(Rom::with_byte_order(bytes, ByteOrder::BigEndian), Err(RomError::BadMagic(0x00371240))));
+    }
+    
+    #[test]
+    fn lenient() {
+        let (rom, problems) = Rom::lenient(z64());
+        assert!(problems.is_empty());
+        assert_eq!(rom.data, z64());
+        
+        let mut truncated = z64()[..0x23].to_vec();
+        ByteOrder::LittleEndian.swap(&mut truncated);
+        let (rom, problems) = Rom::lenient(truncated);
+        assert!(matches!(problems[..], [RomError::TooShort(0x23), RomError::UnalignedSize(0x23)]));
+        assert_eq!(rom.byte_order, ByteOrder::LittleEndian);
+        assert_eq!(rom.header.pc, 0x80000400);
+        assert_eq!(rom.header.image_name[..3], z64()[32..35]);
+        assert_eq!(rom.header.image_name[3..], [0; 17]);
+        assert_eq!(rom.bootcode, [0; 1008]);
+        
+        let (rom, problems) = Rom::lenient(z64()[0x40..].to_vec());
+        assert!(matches!(problems[..], [RomError::TooShort(0xFC0), RomError::UnknownByteOrder(_)]));
+        assert_eq!(rom.byte_order, ByteOrder::BigEndian);
+        assert_eq!(rom.data, z64()[0x40..].to_vec());
+        
+        assert!(matches!(Rom::lenient(Vec::new()).1[..], [RomError::TooShort(0)]));
+    }
+    
+    #[test]
+    fn round_trip() {
+        for order in &[ByteOrder::BigEndian, ByteOrder::ByteSwapped, ByteOrder::LittleEndian] {
+            let mut dump = z64();
+            dump.extend((0..0x2000u32).map(|i| (i * 13) as u8));
+            order.swap(&mut dump);
+            
+            let rom = Rom::new(dump.clone()).unwrap();
+            let header: [u8; HEADER_SIZE] = rom.header().into();
+            
+            assert_eq!(header[..], rom.data()[..HEADER_SIZE]);
+            assert_eq!(Header::from(&header), *rom.header());
+            assert_eq!(rom.to_bytes(*order), dump);
+        }
+    }
+    
+    #[test]
+    fn edits() {
+        let mut rom = Rom::new(z64()).unwrap();
+        let original = rom.data().to_vec();
+        
+        rom.set_name("SUPER MARIO 64").unwrap();
+        rom.set_game_code("NSME").unwrap();
+        rom.set_version(2);
+        rom.set_clockrate(0x0000000F);
+        rom.set_entry_point(0x80246000);
+        
+        let mut bootcode = *rom.bootcode();
+        bootcode[0] = 0x3C0DBFC0;
+        rom.set_bootcode(&bootcode);
+        
+        let data = rom.data();
+        assert_eq!(&data[0x20..0x34], b"SUPER MARIO 64      ");
+        assert_eq!(&data[0x3B..0x40], b"NSME\x02");
+        assert_eq!(data[4..8], [0, 0, 0, 0x0F]);
+        assert_eq!(data[8..12], [0x80, 0x24, 0x60, 0x00]);
+        assert_eq!(data[0x40..0x44], [0x3C, 0x0D, 0xBF, 0xC0]);
+        
+        let changed: Vec<usize> = (0..data.len()).filter(|i| data[*i] != original[*i]).collect();
+        assert!(changed.iter().all(|i| matches!(i, 0x04..=0x0B | 0x20..=0x33 | 0x3B..=0x43)));
+        
+        let reparsed = Rom::new(data.to_vec()).unwrap();
+        assert_eq!(reparsed.header(), rom.header());
+        assert_eq!(reparsed.bootcode()[..], rom.bootcode()[..]);
+        assert_eq!(rom.header().game_code(), *b"NSME");
+        assert_eq!(rom.header().version(), 2);
+        
+        assert_eq!(rom.set_name("A NAME THAT IS TOO LONG"), Err(HeaderError::NameTooLong(23)));
+        assert_eq!(rom.set_game_code("NSM"), Err(HeaderError::InvalidGameCode(String::from("NSM"))));
+        assert_eq!(rom.set_game_code("NSMÉ"), Err(HeaderError::InvalidGameCode(String::from("NSMÉ"))));
+        assert_eq!(rom.header(), reparsed.header());
+        
+        let (mut short, _) = Rom::lenient(z64()[..0x10].to_vec());
+        short.set_version(1);
+        assert_eq!(short.data().len(), HEADER_SIZE);
+        assert_eq!(short.data()[..0x10], z64()[..0x10]);
+        
+        rom.write(0x1000, &[1, 2, 3, 4]);
+        rom.write(0x3C, b"ZE");
+        assert_eq!(rom.header().game_code(), *b"NZEE");
+        assert_eq!(rom.data()[0x1000..0x1004], [1, 2, 3, 4]);
+    }
+}
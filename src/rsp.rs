@@ -0,0 +1,194 @@
+use crate::disassembly::{disassemble, Instruction, Operation};
+
+pub const RSP_CP0_REG_NAMES: [&str; 16] = [
+    "SP_MEM_ADDR", "SP_DRAM_ADDR", "SP_RD_LEN", "SP_WR_LEN", "SP_STATUS", "SP_DMA_FULL", "SP_DMA_BUSY", "SP_SEMAPHORE",
+    "DPC_START", "DPC_END", "DPC_CURRENT", "DPC_STATUS", "DPC_CLOCK", "DPC_BUFBUSY", "DPC_PIPEBUSY", "DPC_TMEM"
+];
+
+pub const VU_CTRL_REG_NAMES: [&str; 3] = ["VCO", "VCC", "VCE"];
+
+pub const VU_ACC_NAMES: [&str; 3] = ["ACC_H", "ACC_M", "ACC_L"];
+
+/// Formats the element specifier of a vector computational instruction (e.g. `[2q]`, `[3h]`, `[5]`). Specifier 1 has
+/// no named form (the hardware treats it like 0), so it's printed raw as `[e1]`.
+pub fn element_suffix(e: u8) -> String {
+    match e {
+        0 => String::new(),
+        1 => String::from("[e1]"),
+        2..=3 => format!("[{}q]", e - 2),
+        4..=7 => format!("[{}h]", e - 4),
+        _ => format!("[{}]", e - 8),
+    }
+}
+
+/// Returns whether the scalar unit of the RSP implements the given (VR4300 decoded) operation.
+pub(crate) fn is_scalar_op(op: Operation) -> bool {
+    use Operation::*;
+    
+    matches!(op,
+        SLL | SRL | SRA | SLLV | SRLV | SRAV | JR | JALR | BREAK |
+        ADD | ADDU | SUB | SUBU | AND | OR | XOR | NOR | SLT | SLTU |
+        BLTZ | BGEZ | BLTZAL | BGEZAL |
+        J | JAL | BEQ | BNE | BLEZ | BGTZ |
+        ADDI | ADDIU | SLTI | SLTIU | ANDI | ORI | XORI | LUI |
+        LB | LH | LW | LBU | LHU | SB | SH | SW |
+        NOP
+    )
+}
+
+pub fn disassemble_rsp(code: u32) -> Instruction {
+    use crate::disassembly::{Operand::*, Operation::*};
+    
+    let op = code >> 26;
+    
+    let rt = ((code >> 16) & 0x1F) as u8;
+    let rd = ((code >> 11) & 0x1F) as u8;
+    
+    match op {
+        // There are only 16 CP0 registers, and words naming any other are left undecoded so they can't alias them.
+        0x10 => match (code >> 21) & 0x1F {
+            0x00 if rd < 16 => Instruction::new2(code, MFC0, Reg(rt), RspCp0Reg(rd)),
+            0x04 if rd < 16 => Instruction::new2(code, MTC0, Reg(rt), RspCp0Reg(rd)),
+            _ => Instruction::new0(code, Unknown)
+        },
+        0x12 => disassemble_cop2(code),
+        0x32 => disassemble_vector_mem(code, true),
+        0x3A => disassemble_vector_mem(code, false),
+        _ => {
+            let instr = disassemble(code);
+            
+            if is_scalar_op(instr.op) {
+                instr
+            } else {
+                Instruction::new0(code, Unknown)
+            }
+        }
+    }
+}
+
+fn disassemble_cop2(code: u32) -> Instruction {
+    use crate::disassembly::{Operand::*, Operation::*};
+    
+    let rt = ((code >> 16) & 0x1F) as u8;
+    let rd = ((code >> 11) & 0x1F) as u8;
+    let lane = ((code >> 7) & 0x0F) as u8;
+    
+    let e = ((code >> 21) & 0x0F) as u8;
+    let vt = ((code >> 16) & 0x1F) as u8;
+    let vs = ((code >> 11) & 0x1F) as u8;
+    let vd = ((code >> 6) & 0x1F) as u8;
+    
+    if (code >> 25) & 1 == 0 {
+        return match (code >> 21) & 0x1F {
+            0x00 => Instruction::new2(code, MFC2, Reg(rt), VRegLane(rd, lane)),
+            0x02 if rd < 3 => Instruction::new2(code, CFC2, Reg(rt), VCtrlReg(rd)),
+            0x04 => Instruction::new2(code, MTC2, Reg(rt), VRegLane(rd, lane)),
+            0x06 if rd < 3 => Instruction::new2(code, CTC2, Reg(rt), VCtrlReg(rd)),
+            _ => Instruction::new0(code, Unknown)
+        };
+    }
+    
+    let compute = match code & 0x3F {
+        0x00 => VMULF,
+        0x01 => VMULU,
+        0x02 => VRNDP,
+        0x03 => VMULQ,
+        0x04 => VMUDL,
+        0x05 => VMUDM,
+        0x06 => VMUDN,
+        0x07 => VMUDH,
+        0x08 => VMACF,
+        0x09 => VMACU,
+        0x0A => VRNDN,
+        0x0B => VMACQ,
+        0x0C => VMADL,
+        0x0D => VMADM,
+        0x0E => VMADN,
+        0x0F => VMADH,
+        0x10 => VADD,
+        0x11 => VSUB,
+        0x13 => VABS,
+        0x14 => VADDC,
+        0x15 => VSUBC,
+        0x20 => VLT,
+        0x21 => VEQ,
+        0x22 => VNE,
+        0x23 => VGE,
+        0x24 => VCL,
+        0x25 => VCH,
+        0x26 => VCR,
+        0x27 => VMRG,
+        0x28 => VAND,
+        0x29 => VNAND,
+        0x2A => VOR,
+        0x2B => VNOR,
+        0x2C => VXOR,
+        0x2D => VNXOR,
+        
+        0x1D => return match e {
+            0x08..=0x0A => Instruction::new2(code, VSAR, VReg(vd), VAcc(e - 0x08)),
+            _ => Instruction::new0(code, Unknown)
+        },
+        
+        // Single lane operations use the `vs` field as the destination element. Only its low 3 bits select one, but
+        // the rest are kept so the word can be encoded back.
+        0x30 => VRCP,
+        0x31 => VRCPL,
+        0x32 => VRCPH,
+        0x33 => VMOV,
+        0x34 => VRSQ,
+        0x35 => VRSQL,
+        0x36 => VRSQH,
+        0x37 => return Instruction::new0(code, VNOP),
+        
+        _ => return Instruction::new0(code, Unknown)
+    };
+    
+    match code & 0x3F {
+        0x30..=0x36 => Instruction::new2(code, compute, VRegLane(vd, vs), VRegElement(vt, e)),
+        _ => Instruction::new3(code, compute, VReg(vd), VReg(vs), VRegElement(vt, e)),
+    }
+}
+
+fn disassemble_vector_mem(code: u32, load: bool) -> Instruction {
+    use crate::disassembly::{Operand::*, Operation::*};
+    
+    let base = ((code >> 21) & 0x1F) as u8;
+    let vt = ((code >> 16) & 0x1F) as u8;
+    let lane = ((code >> 7) & 0x0F) as u8;
+    
+    // 7-bit signed offset, sign extended before scaling by the access size.
+    let offset = (((code & 0x7F) as i8) << 1) >> 1;
+    
+    let (op, shift) = match ((code >> 11) & 0x1F, load) {
+        (0x00, true) => (LBV, 0),
+        (0x01, true) => (LSV, 1),
+        (0x02, true) => (LLV, 2),
+        (0x03, true) => (LDV, 3),
+        (0x04, true) => (LQV, 4),
+        (0x05, true) => (LRV, 4),
+        (0x06, true) => (LPV, 3),
+        (0x07, true) => (LUV, 3),
+        (0x08, true) => (LHV, 4),
+        (0x09, true) => (LFV, 4),
+        (0x0A, true) => (LWV, 4),
+        (0x0B, true) => (LTV, 4),
+        
+        (0x00, false) => (SBV, 0),
+        (0x01, false) => (SSV, 1),
+        (0x02, false) => (SLV, 2),
+        (0x03, false) => (SDV, 3),
+        (0x04, false) => (SQV, 4),
+        (0x05, false) => (SRV, 4),
+        (0x06, false) => (SPV, 3),
+        (0x07, false) => (SUV, 3),
+        (0x08, false) => (SHV, 4),
+        (0x09, false) => (SFV, 4),
+        (0x0A, false) => (SWV, 4),
+        (0x0B, false) => (STV, 4),
+        
+        _ => return Instruction::new0(code, Unknown)
+    };
+    
+    Instruction::new2(code, op, VRegLane(vt, lane), Mem(base, (offset as i16) << shift))
+}
@@ -0,0 +1,264 @@
+use crate::disassembly::{Disassembly, Operand, Operation};
+use crate::rom::Rom;
+use crate::table::{parse_table, TableError};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+
+/// The built-in table, see [`SaveTable::builtin`].
+const BUILTIN: &str = include_str!("../data/savetype.txt");
+
+#[derive(Debug, PartialEq, Eq, Hash, strum_macros::Display, strum_macros::EnumString, Copy, Clone)]
+pub enum SaveType {
+    #[strum(serialize = "eeprom4k")]
+    Eeprom4K,
+    #[strum(serialize = "eeprom16k")]
+    Eeprom16K,
+    /// 256 Kbit battery-backed SRAM, on PI domain 2.
+    #[strum(serialize = "sram")]
+    Sram,
+    /// 1 Mbit FlashRAM, also on PI domain 2.
+    #[strum(serialize = "flashram")]
+    FlashRam,
+    /// Saves to a Controller Pak, on top of or instead of the cartridge.
+    #[strum(serialize = "cpak")]
+    ControllerPak,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+pub enum Confidence {
+    /// A pattern that some games without this hardware also have.
+    Low,
+    /// Code that only makes sense with this hardware, but could be left over and never called.
+    Medium,
+    /// Listed in the save type table.
+    High,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Evidence {
+    /// Listed in the save type table under this code.
+    Table(String),
+    /// An instruction that belongs to a libultra routine or accesses the save hardware.
+    Code { addr: u32, routine: &'static str },
+}
+
+impl Display for Evidence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Evidence::Table(code) => write!(f, "table entry {}", code),
+            Evidence::Code { addr, routine } => write!(f, "{} at {:#010X}", routine, addr),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SaveDetection {
+    pub save_type: SaveType,
+    pub confidence: Confidence,
+    pub evidence: Vec<Evidence>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SaveEntry {
+    /// Either a 2 character game ID or a full 4 character game code.
+    pub code: String,
+    pub save_types: Vec<SaveType>,
+}
+
+/// Maps game codes to save types. Each line of a table is a game ID or full game code, then one or more save types.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SaveTable {
+    pub entries: Vec<SaveEntry>,
+}
+
+impl SaveTable {
+    /// The table bundled from `data/savetype.txt`.
+    pub fn builtin() -> SaveTable {
+        SaveTable::parse(BUILTIN).expect("built-in save type table is invalid")
+    }
+    
+    pub fn parse(text: &str) -> Result<SaveTable, TableError> {
+        let entries = parse_table(text, "<code> <types...>", |fields| {
+            let code = fields.first().filter(|code| (code.len() == 2 || code.len() == 4) && code.is_ascii())?;
+            let save_types = fields[1..].iter().map(|save_type| SaveType::from_str(save_type)).collect::<Result<Vec<SaveType>, _>>().ok()?;
+            
+            match save_types.is_empty() {
+                true => None,
+                false => Some(SaveEntry { code: code.to_string(), save_types }),
+            }
+        })?;
+        
+        Ok(SaveTable { entries })
+    }
+    
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SaveTable, TableError> {
+        SaveTable::parse(&std::fs::read_to_string(path)?)
+    }
+    
+    /// Adds the entries of `other`, so they are found first by [`SaveTable::lookup`].
+    pub fn extend(&mut self, other: SaveTable) {
+        self.entries.extend(other.entries);
+    }
+    
+    /// Finds the entry for a game code, preferring an exact match over one on the game ID alone.
+    pub fn lookup(&self, game_code: [u8; 4]) -> Option<&SaveEntry> {
+        let exact = self.entries.iter().rev().find(|entry| entry.code.as_bytes() == game_code);
+        
+        exact.or_else(|| self.entries.iter().rev().find(|entry| entry.code.as_bytes() == &game_code[1..3]))
+    }
+}
+
+/// Scans code for libultra's save routines and for accesses to the save hardware.
+/// 
+/// EEPROM size is probed at runtime, so code alone can't tell 4K from 16K; 4K is reported, as it is by far the most
+/// common.
+pub fn scan(disasm: &Disassembly) -> Vec<SaveDetection> {
+    use self::{Operand::*, Operation::*};
+    
+    let mut eeprom = Vec::new();
+    let mut flash_register = Vec::new();
+    let mut flash_commands = Vec::new();
+    let mut domain2 = Vec::new();
+    let mut address_crc = Vec::new();
+    let mut data_crc = Vec::new();
+    
+    for (addr, instr) in disasm.iter() {
+        let code = |routine| Evidence::Code { addr, routine };
+        
+        match (instr.op, instr.args) {
+            (ANDI, [_, _, Some(Lit16(0xC000)), _]) => eeprom.push(code("osEepromProbe (CONT_EEPROM | CONT_EEP16K mask)")),
+            (LUI, [_, Some(Lit16(0xA801)), ..]) | (LUI, [_, Some(Lit16(0x0801)), ..]) => flash_register.push(code("osFlashInit (FlashRAM command register)")),
+            (LUI, [_, Some(Lit16(0xE100)), ..]) => flash_commands.push(code("FlashRAM status command")),
+            (LUI, [_, Some(Lit16(0xD200)), ..]) => flash_commands.push(code("FlashRAM execute command")),
+            (LUI, [_, Some(Lit16(0xB400)), ..]) => flash_commands.push(code("FlashRAM write command")),
+            (LUI, [_, Some(Lit16(0x4B00)), ..]) => flash_commands.push(code("FlashRAM sector erase command")),
+            (LUI, [_, Some(Lit16(0xA800)), ..]) => domain2.push(code("PI domain 2 access")),
+            (XORI, [_, _, Some(Lit16(0x15)), _]) => address_crc.push(code("osPfsInit (__osContAddressCrc)")),
+            (XORI, [_, _, Some(Lit16(0x85)), _]) => data_crc.push(code("osPfsInit (__osContDataCrc)")),
+            _ => ()
+        }
+    }
+    
+    let mut detections = Vec::new();
+    let mut detect = |save_type, confidence, evidence: Vec<Evidence>| {
+        detections.push(SaveDetection { save_type, confidence, evidence });
+    };
+    
+    if !eeprom.is_empty() {
+        detect(SaveType::Eeprom4K, Confidence::Low, eeprom);
+    }
+    if !flash_register.is_empty() && !flash_commands.is_empty() {
+        detect(SaveType::FlashRam, Confidence::Medium, [flash_register, flash_commands].concat());
+    } else if !domain2.is_empty() {
+        detect(SaveType::Sram, Confidence::Medium, domain2);
+    }
+    // The Rumble Pak uses the same CRCs, so these alone don't mean the game saves to a Controller Pak.
+    if !address_crc.is_empty() && !data_crc.is_empty() {
+        detect(SaveType::ControllerPak, Confidence::Low, [address_crc, data_crc].concat());
+    }
+    
+    detections
+}
+
+impl Rom {
+    /// Detects the save hardware using the built-in table and a scan of the boot segment. The most confident
+    /// detections come first.
+    pub fn save_types(&self) -> Vec<SaveDetection> {
+        self.save_types_with(&SaveTable::builtin())
+    }
+    
+    pub fn save_types_with(&self, table: &SaveTable) -> Vec<SaveDetection> {
+        let mut detections: Vec<SaveDetection> = Vec::new();
+        
+        if let Some(entry) = table.lookup(self.header().game_code()) {
+            for save_type in &entry.save_types {
+                detections.push(SaveDetection { save_type: *save_type, confidence: Confidence::High, evidence: vec![Evidence::Table(entry.code.clone())] });
+            }
+        }
+        
+        for scanned in scan(&self.boot_segment()) {
+            // The table knows the EEPROM size, which the scan can only guess.
+            let same = |detection: &SaveDetection| detection.save_type == scanned.save_type
+                || matches!((detection.save_type, scanned.save_type), (SaveType::Eeprom16K, SaveType::Eeprom4K));
+            
+            match detections.iter_mut().find(|detection| same(detection)) {
+                Some(detection) => detection.evidence.extend(scanned.evidence),
+                None => detections.push(scanned),
+            }
+        }
+        
+        detections.sort_by_key(|detection| std::cmp::Reverse(detection.confidence));
+        detections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scan, Confidence, Evidence, SaveTable, SaveType};
+    use crate::disassembly::Disassembly;
+    use crate::rom::{test_rom, Rom};
+    use crate::table::TableError;
+    
+    fn rom(game_code: &str, code: &[u32]) -> Rom {
+        let mut rom = test_rom(0x2000).with_pc(0x80000400).with_code(0x1000, code).rom();
+        rom.set_game_code(game_code).unwrap();
+        rom
+    }
+    
+    #[test]
+    fn table() {
+        let table = SaveTable::builtin();
+        
+        assert_eq!(table.lookup(*b"NSME").unwrap().save_types, vec![SaveType::Eeprom4K]);
+        assert_eq!(table.lookup(*b"NKTP").unwrap().save_types, vec![SaveType::Eeprom4K, SaveType::ControllerPak]);
+        assert_eq!(table.lookup(*b"NXXE"), None);
+        
+        let mut table = SaveTable::parse("SM eeprom4k\nNSMJ eeprom16k\n").unwrap();
+        assert_eq!(table.lookup(*b"NSMJ").unwrap().save_types, vec![SaveType::Eeprom16K]);
+        assert_eq!(table.lookup(*b"NSME").unwrap().save_types, vec![SaveType::Eeprom4K]);
+        table.extend(SaveTable::parse("SM sram").unwrap());
+        assert_eq!(table.lookup(*b"NSME").unwrap().save_types, vec![SaveType::Sram]);
+        
+        assert!(matches!(SaveTable::parse("SM"), Err(TableError::Syntax(1, _, _))));
+        assert!(matches!(SaveTable::parse("\nSMX sram"), Err(TableError::Syntax(2, _, _))));
+        assert!(matches!(SaveTable::parse("SM tape"), Err(TableError::Syntax(1, _, _))));
+    }
+    
+    #[test]
+    fn code_scan() {
+        let disasm = Disassembly::from_u32(&[
+            0x3C08A801, // lui t0, 0xA801
+            0x3C09E100, // lui t1, 0xE100
+            0x3C0AA800, // lui t2, 0xA800
+            0x3108C000, // andi t0, t0, 0xC000
+            0x38420015, // xori v0, v0, 0x15
+        ]).at_vaddr(0x80000400);
+        let detections = scan(&disasm);
+        
+        assert_eq!(detections.iter().map(|detection| detection.save_type).collect::<Vec<SaveType>>(), vec![SaveType::Eeprom4K, SaveType::FlashRam]);
+        assert_eq!(detections[0].confidence, Confidence::Low);
+        assert_eq!(detections[0].evidence[0], Evidence::Code { addr: 0x8000040C, routine: "osEepromProbe (CONT_EEPROM | CONT_EEP16K mask)" });
+        assert_eq!(detections[1].evidence.len(), 2);
+        
+        let detections = scan(&Disassembly::from_u32(&[0x3C0AA800, 0x38420015, 0x38420085]));
+        assert_eq!(detections.iter().map(|detection| detection.save_type).collect::<Vec<SaveType>>(), vec![SaveType::Sram, SaveType::ControllerPak]);
+        
+        assert!(scan(&Disassembly::from_u32(&[0x3C088000, 0x00000000])).is_empty());
+    }
+    
+    #[test]
+    fn combined() {
+        let detections = rom("NYSE", &[0x3108C000, 0x3C0AA800]).save_types();
+        
+        assert_eq!(detections.len(), 2);
+        assert_eq!(detections[0].save_type, SaveType::Eeprom16K);
+        assert_eq!(detections[0].confidence, Confidence::High);
+        assert_eq!(detections[0].evidence.len(), 2);
+        assert_eq!(detections[0].evidence[1].to_string(), "osEepromProbe (CONT_EEPROM | CONT_EEP16K mask) at 0x80000400");
+        assert_eq!(detections[1].save_type, SaveType::Sram);
+        assert_eq!(detections[1].confidence, Confidence::Medium);
+        
+        assert!(rom("NXXE", &[]).save_types().is_empty());
+    }
+}
@@ -0,0 +1,366 @@
+use crate::disassembly::{Instruction, Operand, Operation};
+
+/// A register an instruction can read or write.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Register {
+    Gpr(u8),
+    Hi,
+    Lo,
+    Cp0(u8),
+    Fpr(u8),
+    Fcr(u8),
+    Cop(u8),
+    RspCp0(u8),
+    Vpr(u8),
+    VCtrl(u8),
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Category {
+    Alu,
+    Shift,
+    MulDiv,
+    Load,
+    Store,
+    Branch,
+    Jump,
+    Trap,
+    /// SYSCALL, BREAK and SYNC.
+    System,
+    /// CP0 moves, TLB maintenance, ERET and CACHE.
+    Cop0,
+    /// FPU arithmetic, conversions, compares and moves.
+    Cop1,
+    /// Moves and operations on a coprocessor the VR4300 doesn't have.
+    Coprocessor,
+    /// RSP vector unit computation and moves.
+    Vector,
+    Nop,
+    Unknown,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum AccessKind {
+    Load,
+    Store,
+}
+
+/// Describes the memory access of a load or store.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct MemAccess {
+    pub kind: AccessKind,
+    /// Width in bytes. For unaligned (LWL/LWR etc) and RSP vector accesses, this is the maximum width.
+    pub width: u8,
+    /// Whether a loaded value is sign extended into the destination register.
+    pub signed: bool,
+}
+
+/// How the register operands of an instruction are used.
+#[derive(PartialEq, Copy, Clone)]
+enum Flow {
+    /// Every register operand is read.
+    Reads,
+    /// The first operand is written, the rest are read.
+    WritesFirst,
+    /// The first operand is both read and written, the rest are read.
+    ReadsWritesFirst,
+    /// The last register operand is written, the rest are read (MTC0, CTC1, etc).
+    WritesLast,
+}
+
+impl Instruction {
+    pub fn category(&self) -> Category {
+        use Operation::*;
+        
+        match self.op {
+            NOP => Category::Nop,
+            Unknown => Category::Unknown,
+            
+            ADD | ADDI | ADDIU | ADDU | AND | ANDI | DADD | DADDI | DADDIU | DADDU | DSUB | DSUBU |
+            LUI | NOR | OR | ORI | SLT | SLTI | SLTIU | SLTU | SUB | SUBU | XOR | XORI | MFHI | MFLO | MTHI | MTLO => Category::Alu,
+            
+            SLL | SLLV | SRA | SRAV | SRL | SRLV | DSLL | DSLLV | DSLL32 | DSRA | DSRAV | DSRA32 | DSRL | DSRLV | DSRL32 => Category::Shift,
+            
+            MULT | MULTU | DIV | DIVU | DMULT | DMULTU | DDIV | DDIVU => Category::MulDiv,
+            
+            J | JAL | JR | JALR => Category::Jump,
+            
+            TEQ | TEQI | TGE | TGEI | TGEIU | TGEU | TLT | TLTI | TLTIU | TLTU | TNE | TNEI => Category::Trap,
+            
+            SYSCALL | BREAK | SYNC => Category::System,
+            
+            MFC0 | MTC0 | DMFC0 | DMTC0 | TLBP | TLBR | TLBWI | TLBWR | ERET | CACHE => Category::Cop0,
+            
+            MFCz | MTCz | CFCz | CTCz | COPz => Category::Coprocessor,
+            
+            _ if self.is_branch() => Category::Branch,
+            _ => match self.mem_access() {
+                Some(MemAccess { kind: AccessKind::Load, .. }) => Category::Load,
+                Some(MemAccess { kind: AccessKind::Store, .. }) => Category::Store,
+                None if self.args.iter().flatten().any(|arg| matches!(arg, Operand::Cp1Reg(_) | Operand::Cp1CtrlReg(_))) => Category::Cop1,
+                None => Category::Vector,
+            },
+        }
+    }
+    
+    /// Any PC-relative branch, including the likely and linking variants.
+    pub fn is_branch(&self) -> bool {
+        use Operation::*;
+        
+        matches!(self.op,
+            BEQ | BEQL | BNE | BNEL | BLEZ | BLEZL | BGTZ | BGTZL |
+            BLTZ | BLTZL | BGEZ | BGEZL | BLTZAL | BLTZALL | BGEZAL | BGEZALL |
+            BCzF | BCzFL | BCzT | BCzTL | BC1F | BC1FL | BC1T | BC1TL
+        )
+    }
+    
+    /// J, JAL, JR and JALR.
+    pub fn is_jump(&self) -> bool {
+        use Operation::*;
+        
+        matches!(self.op, J | JAL | JR | JALR)
+    }
+    
+    /// Jumps and branches that write the return address.
+    pub fn is_call(&self) -> bool {
+        use Operation::*;
+        
+        matches!(self.op, JAL | JALR | BLTZAL | BLTZALL | BGEZAL | BGEZALL)
+    }
+    
+    /// `jr ra`.
+    pub fn is_return(&self) -> bool {
+        self.op == Operation::JR && self.args[0] == Some(Operand::Reg(31))
+    }
+    
+    /// Branch likely instructions, which nullify their delay slot when the branch isn't taken.
+    pub fn is_likely(&self) -> bool {
+        use Operation::*;
+        
+        matches!(self.op,
+            BEQL | BNEL | BLEZL | BGTZL | BLTZL | BGEZL | BLTZALL | BGEZALL | BCzFL | BCzTL | BC1FL | BC1TL
+        )
+    }
+    
+    pub fn has_delay_slot(&self) -> bool {
+        self.is_branch() || self.is_jump()
+    }
+    
+    pub fn mem_access(&self) -> Option<MemAccess> {
+        use self::{AccessKind::*, Operation::*};
+        
+        let (kind, width, signed) = match self.op {
+            LB => (Load, 1, true),
+            LBU => (Load, 1, false),
+            LH => (Load, 2, true),
+            LHU => (Load, 2, false),
+            LW | LL | LWL | LWR => (Load, 4, true),
+            LWU => (Load, 4, false),
+            LWC1 | LWCz => (Load, 4, false),
+            LD | LLD | LDL | LDR | LDC1 | LDCz => (Load, 8, false),
+            
+            SB => (Store, 1, false),
+            SH => (Store, 2, false),
+            SW | SC | SWL | SWR | SWC1 | SWCz => (Store, 4, false),
+            SD | SCD | SDL | SDR | SDC1 | SDCz => (Store, 8, false),
+            
+            LBV => (Load, 1, false),
+            LSV => (Load, 2, false),
+            LLV => (Load, 4, false),
+            LDV | LPV | LUV => (Load, 8, false),
+            LQV | LRV | LHV | LFV | LWV | LTV => (Load, 16, false),
+            
+            SBV => (Store, 1, false),
+            SSV => (Store, 2, false),
+            SLV => (Store, 4, false),
+            SDV | SPV | SUV => (Store, 8, false),
+            SQV | SRV | SHV | SFV | SWV | STV => (Store, 16, false),
+            
+            _ => return None
+        };
+        
+        Some(MemAccess { kind, width, signed })
+    }
+    
+    /// C.cond.fmt, which only writes the condition bit in FCR31.
+    fn is_fpu_compare(&self) -> bool {
+        use Operation::*;
+        
+        matches!(self.op,
+            C_F_S | C_UN_S | C_EQ_S | C_UEQ_S | C_OLT_S | C_ULT_S | C_OLE_S | C_ULE_S |
+            C_SF_S | C_NGLE_S | C_SEQ_S | C_NGL_S | C_LT_S | C_NGE_S | C_LE_S | C_NGT_S |
+            C_F_D | C_UN_D | C_EQ_D | C_UEQ_D | C_OLT_D | C_ULT_D | C_OLE_D | C_ULE_D |
+            C_SF_D | C_NGLE_D | C_SEQ_D | C_NGL_D | C_LT_D | C_NGE_D | C_LE_D | C_NGT_D
+        )
+    }
+    
+    fn flow(&self) -> Flow {
+        use Operation::*;
+        
+        match self.op {
+            MTC0 | DMTC0 | MTC1 | DMTC1 | CTC1 | MTCz | CTCz | MTC2 | CTC2 => Flow::WritesLast,
+            LWL | LWR | LDL | LDR | SC | SCD => Flow::ReadsWritesFirst,
+            JR | MTHI | MTLO | MULT | MULTU | DIV | DIVU | DMULT | DMULTU | DDIV | DDIVU | CACHE => Flow::Reads,
+            _ if self.is_branch() || self.is_fpu_compare() || self.category() == Category::Trap => Flow::Reads,
+            _ => match self.mem_access() {
+                Some(MemAccess { kind: AccessKind::Store, .. }) => Flow::Reads,
+                _ => Flow::WritesFirst,
+            },
+        }
+    }
+    
+    /// Registers read by this instruction. Reads of `r0` are left out, as they are always zero.
+    pub fn reads(&self) -> Vec<Register> {
+        use Operation::*;
+        
+        let operands: Vec<&Operand> = self.args.iter().flatten().collect();
+        let flow = self.flow();
+        
+        let mut regs = Vec::new();
+        for (i, operand) in operands.iter().enumerate() {
+            let written_only = match flow {
+                Flow::WritesFirst => i == 0,
+                Flow::WritesLast => i == operands.len() - 1,
+                _ => false,
+            };
+            
+            match operand {
+                Operand::Mem(base, _) => regs.push(Register::Gpr(*base)),
+                _ if written_only => (),
+                _ => regs.extend(register_of(operand)),
+            }
+        }
+        
+        match self.op {
+            MFHI => regs.push(Register::Hi),
+            MFLO => regs.push(Register::Lo),
+            BC1F | BC1FL | BC1T | BC1TL => regs.push(Register::Fcr(31)),
+            _ => ()
+        }
+        
+        regs.retain(|reg| *reg != Register::Gpr(0));
+        regs
+    }
+    
+    /// Registers written by this instruction. Writes to `r0` are left out, as they have no effect.
+    pub fn writes(&self) -> Vec<Register> {
+        use Operation::*;
+        
+        let operands: Vec<&Operand> = self.args.iter().flatten().filter(|arg| register_of(arg).is_some()).collect();
+        
+        let mut regs = Vec::new();
+        match self.flow() {
+            Flow::WritesFirst | Flow::ReadsWritesFirst => regs.extend(operands.first().and_then(|arg| register_of(arg))),
+            Flow::WritesLast => regs.extend(operands.last().and_then(|arg| register_of(arg))),
+            Flow::Reads => (),
+        }
+        
+        match self.op {
+            MTHI => regs.push(Register::Hi),
+            MTLO => regs.push(Register::Lo),
+            MULT | MULTU | DIV | DIVU | DMULT | DMULTU | DDIV | DDIVU => regs.extend_from_slice(&[Register::Hi, Register::Lo]),
+            JAL | BLTZAL | BLTZALL | BGEZAL | BGEZALL => regs.push(Register::Gpr(31)),
+            _ if self.is_fpu_compare() => regs.push(Register::Fcr(31)),
+            _ => ()
+        }
+        
+        regs.retain(|reg| *reg != Register::Gpr(0));
+        regs
+    }
+}
+
+fn register_of(operand: &Operand) -> Option<Register> {
+    use Operand::*;
+    
+    match operand {
+        Reg(reg) => Some(Register::Gpr(*reg)),
+        Cp0Reg(reg) => Some(Register::Cp0(*reg)),
+        Cp1Reg(reg) => Some(Register::Fpr(*reg)),
+        Cp1CtrlReg(reg) => Some(Register::Fcr(*reg)),
+        CopReg(reg) => Some(Register::Cop(*reg)),
+        RspCp0Reg(reg) => Some(Register::RspCp0(*reg)),
+        VReg(reg) | VRegElement(reg, _) | VRegLane(reg, _) => Some(Register::Vpr(*reg)),
+        VCtrlReg(reg) => Some(Register::VCtrl(*reg)),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessKind::*, Category, MemAccess, Register::*};
+    use crate::disassembly::{disassemble_target, Target};
+    
+    #[test]
+    fn register_flow() {
+        for (code, category, reads, writes) in &[
+            (0x00851021, Category::Alu, vec![Gpr(4), Gpr(5)], vec![Gpr(2)]), // addu v0, a0, a1
+            (0x00041021, Category::Alu, vec![Gpr(4)], vec![Gpr(2)]), // addu v0, zr, a0
+            (0x00850018, Category::MulDiv, vec![Gpr(4), Gpr(5)], vec![Hi, Lo]), // mult a0, a1
+            (0x0085001A, Category::MulDiv, vec![Gpr(4), Gpr(5)], vec![Hi, Lo]), // div a0, a1
+            (0x00001010, Category::Alu, vec![Hi], vec![Gpr(2)]), // mfhi v0
+            (0x00800013, Category::Alu, vec![Gpr(4)], vec![Lo]), // mtlo a0
+            (0x8C820004, Category::Load, vec![Gpr(4)], vec![Gpr(2)]), // lw v0, 0x4(a0)
+            (0x88820004, Category::Load, vec![Gpr(2), Gpr(4)], vec![Gpr(2)]), // lwl v0, 0x4(a0)
+            (0xE0820004, Category::Store, vec![Gpr(2), Gpr(4)], vec![Gpr(2)]), // sc v0, 0x4(a0)
+            (0xAC820004, Category::Store, vec![Gpr(2), Gpr(4)], vec![]), // sw v0, 0x4(a0)
+            (0xAC800004, Category::Store, vec![Gpr(4)], vec![]), // sw zr, 0x4(a0)
+            (0x40886000, Category::Cop0, vec![Gpr(8)], vec![Cp0(12)]), // mtc0 t0, SR
+            (0x40086000, Category::Cop0, vec![Cp0(12)], vec![Gpr(8)]), // mfc0 t0, SR
+            (0x44C8F800, Category::Cop1, vec![Gpr(8)], vec![Fcr(31)]), // ctc1 t0, FCR31
+            (0x4448F800, Category::Cop1, vec![Fcr(31)], vec![Gpr(8)]), // cfc1 t0, FCR31
+            (0x46062032, Category::Cop1, vec![Fpr(4), Fpr(6)], vec![Fcr(31)]), // c.eq.s f4, f6
+            (0x45010003, Category::Branch, vec![Fcr(31)], vec![]), // bc1t
+            (0x45000003, Category::Branch, vec![Fcr(31)], vec![]), // bc1f
+            (0x10850003, Category::Branch, vec![Gpr(4), Gpr(5)], vec![]), // beq a0, a1
+            (0x04910003, Category::Branch, vec![Gpr(4)], vec![Gpr(31)]), // bgezal a0
+            (0x0C000100, Category::Jump, vec![], vec![Gpr(31)]), // jal 0x400
+            (0x03E00008, Category::Jump, vec![Gpr(31)], vec![]), // jr ra
+            (0x0080F809, Category::Jump, vec![Gpr(4)], vec![Gpr(31)]), // jalr a0
+            (0x00801009, Category::Jump, vec![Gpr(4)], vec![Gpr(2)]), // jalr v0, a0
+            (0x00000000, Category::Nop, vec![], vec![]),
+        ] {
+            let instr = disassemble_target(*code, Target::VR4300);
+            assert_eq!(instr.category(), *category, "{:#010X}", code);
+            assert_eq!(instr.reads(), *reads, "{:#010X} reads", code);
+            assert_eq!(instr.writes(), *writes, "{:#010X} writes", code);
+        }
+    }
+    
+    #[test]
+    fn mem_access() {
+        let access = |kind, width, signed| Some(MemAccess { kind, width, signed });
+        
+        for (code, expected) in &[
+            (0x80820004, access(Load, 1, true)), // lb v0, 0x4(a0)
+            (0x90820004, access(Load, 1, false)), // lbu v0, 0x4(a0)
+            (0x88820004, access(Load, 4, true)), // lwl v0, 0x4(a0)
+            (0xFC820008, access(Store, 8, false)), // sd v0, 0x8(a0)
+            (0xD4820008, access(Load, 8, false)), // ldc1 f2, 0x8(a0)
+            (0xE4820004, access(Store, 4, false)), // swc1 f2, 0x4(a0)
+            (0x00851021, None), // addu v0, a0, a1
+        ] {
+            assert_eq!(disassemble_target(*code, Target::VR4300).mem_access(), *expected, "{:#010X}", code);
+        }
+    }
+    
+    #[test]
+    fn control_flow() {
+        for (code, likely, call, ret, delay_slot) in &[
+            (0x50850003, true, false, false, true), // beql a0, a1
+            (0x10850003, false, false, false, true), // beq a0, a1
+            (0x0C000100, false, true, false, true), // jal 0x400
+            (0x0080F809, false, true, false, true), // jalr a0
+            (0x04110003, false, true, false, true), // bal
+            (0x03E00008, false, false, true, true), // jr ra
+            (0x01000008, false, false, false, true), // jr t0
+            (0x42000018, false, false, false, false), // eret
+            (0x0000000C, false, false, false, false), // syscall
+        ] {
+            let instr = disassemble_target(*code, Target::VR4300);
+            assert_eq!(instr.is_likely(), *likely, "{:#010X} is_likely", code);
+            assert_eq!(instr.is_call(), *call, "{:#010X} is_call", code);
+            assert_eq!(instr.is_return(), *ret, "{:#010X} is_return", code);
+            assert_eq!(instr.has_delay_slot(), *delay_slot, "{:#010X} has_delay_slot", code);
+        }
+    }
+}
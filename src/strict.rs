@@ -0,0 +1,208 @@
+use crate::disassembly::{disassemble, disassemble_target, Disassembly, Instruction, Operation, Target};
+use std::fmt::{Display, Formatter};
+
+/// Why a word is not a valid instruction for the target.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum DecodeError {
+    /// The primary opcode (bits 31..26) is reserved.
+    ReservedOpcode(u8),
+    /// A secondary opcode (function, `rt`, `fmt`, etc) is reserved within the named opcode group.
+    ReservedFunction(&'static str, u8),
+    /// A field that must be zero is set.
+    ReservedField(Operation, &'static str),
+    /// A valid MIPS encoding that the target doesn't implement (e.g. COP2/COP3 on the VR4300).
+    Unimplemented(Operation),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use DecodeError::*;
+        
+        match self {
+            ReservedOpcode(op) => write!(f, "reserved opcode {:#04X}", op),
+            ReservedFunction(group, func) => write!(f, "reserved {} function {:#04X}", group, func),
+            ReservedField(op, field) => write!(f, "{} has reserved field {} set", op, field),
+            Unimplemented(op) => write!(f, "{} is not implemented by the target", op),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+const RS: u32 = 0x1F << 21;
+const RT: u32 = 0x1F << 16;
+const RD: u32 = 0x1F << 11;
+const SA: u32 = 0x1F << 6;
+
+/// Bits that must be zero for the given operation, along with a name for the error.
+fn reserved_bits(op: Operation) -> Option<(u32, &'static str)> {
+    use Operation::*;
+    
+    match op {
+        SLL | SRL | SRA | DSLL | DSRL | DSRA | DSLL32 | DSRL32 | DSRA32 => Some((RS, "rs")),
+        SLLV | SRLV | SRAV | DSLLV | DSRLV | DSRAV => Some((SA, "sa")),
+        ADD | ADDU | SUB | SUBU | AND | OR | XOR | NOR | SLT | SLTU | DADD | DADDU | DSUB | DSUBU => Some((SA, "sa")),
+        JR => Some((RT | RD | SA, "rt/rd/sa")),
+        JALR => Some((RT | SA, "rt/sa")),
+        SYNC => Some((RS | RT | RD, "rs/rt/rd")),
+        MFHI | MFLO => Some((RS | RT | SA, "rs/rt/sa")),
+        MTHI | MTLO => Some((RT | RD | SA, "rt/rd/sa")),
+        MULT | MULTU | DIV | DIVU | DMULT | DMULTU | DDIV | DDIVU => Some((RD | SA, "rd/sa")),
+        BLEZ | BLEZL | BGTZ | BGTZL => Some((RT, "rt")),
+        LUI => Some((RS, "rs")),
+        
+        MFC0 | MTC0 | DMFC0 | DMTC0 | MFC1 | MTC1 | DMFC1 | DMTC1 | CFC1 | CTC1 => Some((0x7FF, "bits 10..0")),
+        TLBR | TLBWI | TLBWR | TLBP | ERET => Some((0x01FFFFC0, "bits 24..6")),
+        CFC2 | CTC2 => Some((0x7FF, "bits 10..0")),
+        VRCP | VRCPL | VRCPH | VMOV | VRSQ | VRSQL | VRSQH => Some((0x18 << 11, "vs bits 4..3")),
+        VNOP => Some((RD | SA, "vs/vd")),
+        
+        SQRT_S | SQRT_D | ABS_S | ABS_D | MOV_S | MOV_D | NEG_S | NEG_D |
+        ROUND_L_S | ROUND_L_D | ROUND_W_S | ROUND_W_D | TRUNC_L_S | TRUNC_L_D | TRUNC_W_S | TRUNC_W_D |
+        CEIL_L_S | CEIL_L_D | CEIL_W_S | CEIL_W_D | FLOOR_L_S | FLOOR_L_D | FLOOR_W_S | FLOOR_W_D |
+        CVT_S_D | CVT_S_W | CVT_S_L | CVT_D_S | CVT_D_W | CVT_D_L | CVT_W_S | CVT_W_D | CVT_L_S | CVT_L_D => Some((RT, "ft")),
+        
+        C_F_S | C_UN_S | C_EQ_S | C_UEQ_S | C_OLT_S | C_ULT_S | C_OLE_S | C_ULE_S |
+        C_SF_S | C_NGLE_S | C_SEQ_S | C_NGL_S | C_LT_S | C_NGE_S | C_LE_S | C_NGT_S |
+        C_F_D | C_UN_D | C_EQ_D | C_UEQ_D | C_OLT_D | C_ULT_D | C_OLE_D | C_ULE_D |
+        C_SF_D | C_NGLE_D | C_SEQ_D | C_NGL_D | C_LT_D | C_NGE_D | C_LE_D | C_NGT_D => Some((SA, "fd")),
+        
+        _ => None
+    }
+}
+
+/// Explains why the lenient decoder fell back to [`Operation::Unknown`].
+fn unknown_reason(code: u32, target: Target) -> DecodeError {
+    let op = (code >> 26) as u8;
+    let rs = ((code >> 21) & 0x1F) as u8;
+    let rt = ((code >> 16) & 0x1F) as u8;
+    let rd = ((code >> 11) & 0x1F) as u8;
+    let funct = (code & 0x3F) as u8;
+    
+    match (target, op) {
+        // The RSP has 16 CP0 registers, and no COP1 or COP3.
+        (Target::RSP, 0x10) if rs == 0x00 && rd >= 16 => DecodeError::ReservedField(Operation::MFC0, "rd bit 4"),
+        (Target::RSP, 0x10) if rs == 0x04 && rd >= 16 => DecodeError::ReservedField(Operation::MTC0, "rd bit 4"),
+        (Target::RSP, 0x11) | (Target::RSP, 0x13) => DecodeError::ReservedOpcode(op),
+        (Target::RSP, 0x32) | (Target::RSP, 0x3A) => DecodeError::ReservedFunction("vector load/store", rd),
+        (Target::RSP, 0x12) if code & (1 << 25) != 0 => DecodeError::ReservedFunction("COP2", funct),
+        (Target::RSP, 0x12) => DecodeError::ReservedFunction("COP2", rs),
+        // VR4300 instructions the RSP's scalar unit leaves out, such as the 64-bit and multiply/divide ones and ERET.
+        (Target::RSP, _) if disassemble(code).op != Operation::Unknown => DecodeError::Unimplemented(disassemble(code).op),
+        (_, 0x00) => DecodeError::ReservedFunction("SPECIAL", funct),
+        (_, 0x01) => DecodeError::ReservedFunction("REGIMM", rt),
+        (_, 0x10..=0x13) if code & (1 << 25) != 0 => DecodeError::ReservedFunction("COPz", funct),
+        (_, 0x10..=0x13) if rs == 0x08 => DecodeError::ReservedFunction("BCz", rt),
+        (_, 0x10..=0x13) => DecodeError::ReservedFunction("COPz", rs),
+        _ => DecodeError::ReservedOpcode(op),
+    }
+}
+
+/// Decodes `code`, rejecting words that the lenient [`disassemble_target`] would accept or mark as unknown.
+pub fn disassemble_strict(code: u32, target: Target) -> Result<Instruction, DecodeError> {
+    use Operation::*;
+    
+    let instr = disassemble_target(code, target);
+    let cpz = (code >> 26) & 0x03;
+    
+    if instr.op == Unknown {
+        return Err(unknown_reason(code, target));
+    }
+    
+    if target == Target::VR4300 {
+        match instr.op {
+            LWCz | LDCz | SWCz | SDCz | MFCz | MTCz | CFCz | CTCz | BCzF | BCzFL | BCzT | BCzTL => return Err(DecodeError::Unimplemented(instr.op)),
+            COPz if cpz == 0 => return Err(DecodeError::ReservedFunction("COP0", (code & 0x3F) as u8)),
+            COPz => return Err(DecodeError::Unimplemented(instr.op)),
+            _ => ()
+        }
+    }
+    
+    match reserved_bits(instr.op) {
+        Some((mask, field)) if code & mask != 0 => Err(DecodeError::ReservedField(instr.op, field)),
+        _ => Ok(instr),
+    }
+}
+
+impl Disassembly {
+    /// Runs every word through [`disassemble_strict`], returning the index and reason of each invalid one.
+    pub fn strict_errors(&self) -> Vec<(usize, DecodeError)> {
+        self.raw.iter().enumerate()
+            .filter_map(|(i, code)| disassemble_strict(*code, self.target).err().map(|err| (i, err)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{disassemble_strict, reserved_bits, unknown_reason, DecodeError::*, RD, RS, RT, SA};
+    use crate::disassembly::{Disassembly, Operation::*, Target::*};
+    
+    #[test]
+    fn rejects_invalid_words() {
+        for (code, target, err) in &[
+            (0x01000808, VR4300, ReservedField(JR, "rt/rd/sa")), // jr t0 with rd = 1
+            (0x00284000, VR4300, ReservedField(SLL, "rs")), // sll t0, t0, 0 with rs = 1
+            (0x40086001, VR4300, ReservedField(MFC0, "bits 10..0")),
+            (0x46041005, VR4300, ReservedField(ABS_S, "ft")), // abs.s with ft = 4
+            (0xC8000000, VR4300, Unimplemented(LWCz)), // lwc2
+            (0x4A000000, VR4300, Unimplemented(COPz)),
+            (0x00000001, VR4300, ReservedFunction("SPECIAL", 0x01)),
+            (0x04040000, VR4300, ReservedFunction("REGIMM", 0x04)),
+            (0x42000003, VR4300, ReservedFunction("COP0", 0x03)),
+            (0x70000000, VR4300, ReservedOpcode(0x1C)),
+            (0xC8006000, RSP, ReservedFunction("vector load/store", 0x0C)), // lwc2 with rd = 12
+            (0xE8006000, RSP, ReservedFunction("vector load/store", 0x0C)), // swc2 with rd = 12
+            (0x4A00003F, RSP, ReservedFunction("COP2", 0x3F)),
+            (0x4A2DF477, RSP, ReservedField(VNOP, "vs/vd")),
+            (0x485314DB, RSP, ReservedField(CFC2, "bits 10..0")), // cfc2 s3, VCE
+            (0x4083D800, RSP, ReservedField(MTC0, "rd bit 4")), // mtc0 v1, $27
+            (0x4A15E8F4, RSP, ReservedField(VRSQ, "vs bits 4..3")),
+            (0x44882000, RSP, ReservedOpcode(0x11)), // mtc1 t0, f4
+            (0x4C000000, RSP, ReservedOpcode(0x13)),
+            (0x42000018, RSP, Unimplemented(ERET)),
+            (0x00850018, RSP, Unimplemented(MULT)),
+            (0xDC820000, RSP, Unimplemented(LD)),
+        ] {
+            assert_eq!(disassemble_strict(*code, *target).map(|instr| instr.op), Err(*err), "{:#010X}", code);
+        }
+    }
+    
+    #[test]
+    fn accepts_valid_words() {
+        for (code, target, op) in &[
+            (0x27BDFFE8, VR4300, ADDIU), // addiu sp, sp, -0x18
+            (0x01000008, VR4300, JR), // jr t0
+            (0x00084100, VR4300, SLL), // sll t0, t0, 4
+            (0x40086000, VR4300, MFC0), // mfc0 t0, SR
+            (0x42000018, VR4300, ERET),
+            (0x48000000, RSP, MFC2),
+            (0x48420800, RSP, CFC2),
+            (0x40805800, RSP, MTC0), // mtc0 zr, DPC_STATUS
+            (0x4BE22874, RSP, VRSQ),
+            (0x4A000037, RSP, VNOP),
+        ] {
+            assert_eq!(disassemble_strict(*code, *target).map(|instr| instr.op), Ok(*op), "{:#010X}", code);
+        }
+    }
+    
+    #[test]
+    fn reasons() {
+        assert_eq!(reserved_bits(JR), Some((RT | RD | SA, "rt/rd/sa")));
+        assert_eq!(reserved_bits(SLL), Some((RS, "rs")));
+        assert_eq!(reserved_bits(ADDIU), None);
+        
+        assert_eq!(unknown_reason(0x0000003F, VR4300), ReservedFunction("SPECIAL", 0x3F));
+        assert_eq!(unknown_reason(0x44E00000, VR4300), ReservedFunction("COPz", 0x07));
+        assert_eq!(unknown_reason(0x451F0000, VR4300), ReservedFunction("BCz", 0x1F));
+        assert_eq!(unknown_reason(0x7C000000, VR4300), ReservedOpcode(0x1F));
+    }
+    
+    #[test]
+    fn strict_errors() {
+        let disasm = Disassembly::from_u32(&[0x27BDFFE8, 0x00000001, 0x03E00008, 0x01000808]);
+        assert_eq!(disasm.strict_errors(), vec![(1, ReservedFunction("SPECIAL", 0x01)), (3, ReservedField(JR, "rt/rd/sa"))]);
+        
+        assert!(Disassembly::from_u32(&[0x27BDFFE8, 0x03E00008]).strict_errors().is_empty());
+    }
+}
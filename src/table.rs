@@ -0,0 +1,62 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub enum TableError {
+    Io(std::io::Error),
+    /// A line that doesn't match the table's format, along with its line number and the expected format.
+    Syntax(usize, String, &'static str),
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::Io(err) => write!(f, "{}", err),
+            TableError::Syntax(line, text, format) => write!(f, "line {}: expected `{}`, found `{}`", line, format, text),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+impl From<std::io::Error> for TableError {
+    fn from(err: std::io::Error) -> Self {
+        TableError::Io(err)
+    }
+}
+
+/// Parses a plain text table with one whitespace separated entry per line. `#` starts a comment, and blank lines are
+/// skipped. `parse_entry` gets the fields of each remaining line, and returns `None` if they don't match `format`.
+pub(crate) fn parse_table<T, F: Fn(&[&str]) -> Option<T>>(text: &str, format: &'static str, parse_entry: F) -> Result<Vec<T>, TableError> {
+    let mut entries = Vec::new();
+    
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        entries.push(parse_entry(&fields).ok_or_else(|| TableError::Syntax(i + 1, line.to_string(), format))?);
+    }
+    
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_table, TableError};
+    
+    #[test]
+    fn fields_and_errors() {
+        let text = "# header\n\na 1  # trailing comment\n  b   2 3\n";
+        let rows = parse_table(text, "<name> <values...>", |fields| Some(fields.iter().map(|field| field.to_string()).collect::<Vec<String>>()));
+        assert_eq!(rows.unwrap(), vec![vec!["a", "1"], vec!["b", "2", "3"]]);
+        
+        let err = parse_table(text, "<name> <value>", |fields| match fields.len() {
+            2 => Some(()),
+            _ => None,
+        }).unwrap_err();
+        assert!(matches!(err, TableError::Syntax(4, _, _)));
+        assert_eq!(err.to_string(), "line 4: expected `<name> <value>`, found `b   2 3`");
+    }
+}